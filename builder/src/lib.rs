@@ -2,30 +2,67 @@ use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 use quote::quote;
 
+/// Generates a `FooBuilder` with one setter per field and a `build` method.
+///
+/// Field attributes:
+///
+/// - `#[builder(each = "name")]` on a `Vec<T>` field adds a setter that pushes
+///   one element at a time.
+///
+/// Container attributes:
+///
+/// - `#[builder(build_fn(take))]` generates `build(&mut self)`, which moves the
+///   values out of the builder instead of cloning them and leaves the builder
+///   empty again. Use this when a field is not `Clone`, such as `&mut T`,
+///   `std::fs::File` or `Box<dyn Write>`. If a required field is missing the
+///   builder is left untouched.
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let name = &ast.ident;
     let builder_name = format!("{}Builder", name);
     let builder_ident = syn::Ident::new(&builder_name, name.span());
-    let fields = if let syn::Data::Struct(syn::DataStruct { 
-        fields: syn::Fields::Named(syn::FieldsNamed {ref named, ..}), 
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let container_attrs = match parse_container_attrs(&ast.attrs) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let fields = if let syn::Data::Struct(syn::DataStruct {
+        fields: syn::Fields::Named(syn::FieldsNamed {ref named, ..}),
         ..
     }) = ast.data {
         named
     } else {
-        unimplemented!();
+        return syn::Error::new_spanned(&ast, "Builder can only be derived for structs with named fields")
+            .to_compile_error()
+            .into();
+    };
+
+    let attrs = match parse_all_field_attrs(fields) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
     };
+    if !container_attrs.take {
+        if let Some(f) = fields.iter().find(|f| matches!(f.ty, syn::Type::Reference(ref r) if r.mutability.is_some())) {
+            return syn::Error::new_spanned(
+                &f.ty,
+                "`&mut` fields cannot be cloned out of the builder; add `#[builder(build_fn(take))]` to the struct",
+            )
+            .to_compile_error()
+            .into();
+        }
+    }
+    let fields: Vec<(&syn::Field, &FieldAttrs)> = fields.iter().zip(&attrs).collect();
 
-    let fields_after_option_types = fields.iter().map(|f| {
+    let fields_after_option_types = fields.iter().map(|(f, attrs)| {
         let field_name = &f.ident;
         let ty = &f.ty;
-        if unwrap_wrapper_t("Option", ty).is_some() || builder_of(&f) {
+        if unwrap_wrapper_t("Option", ty).is_some() || attrs.each.is_some() {
             return quote! { #field_name: #ty };
         }
         quote! { #field_name: std::option::Option<#ty> }
     });
-    let methods = fields.iter().map(|f| {
+    let methods = fields.iter().map(|(f, attrs)| {
         let field_name = &f.ident;
         let ty = &f.ty;
         let setter_method = if let Some(inner_ty) = unwrap_wrapper_t("Option",ty) {
@@ -35,7 +72,7 @@ pub fn derive(input: TokenStream) -> TokenStream {
                     self
                 }
             }
-        } else if builder_of(&f) {
+        } else if attrs.each.is_some() {
             quote! {
                 fn #field_name(&mut self, #field_name: #ty) -> &mut Self {
                     self.#field_name = #field_name;
@@ -50,7 +87,7 @@ pub fn derive(input: TokenStream) -> TokenStream {
                 }
             }
         };
-        match extended_methods(&f) {
+        match extended_method(f, attrs) {
             None => setter_method,
             Some((true, extend_method)) => extend_method,
             Some((false, extend_method)) =>  quote! {
@@ -60,11 +97,26 @@ pub fn derive(input: TokenStream) -> TokenStream {
         }
 
     });
-   
-    let build_method = fields.iter().map(|f| {
+
+    let build_method = fields.iter().map(|(f, attrs)| {
         let field_name = &f.ident;
         let ty = &f.ty;
-        if unwrap_wrapper_t("Option", ty).is_some() || builder_of(&f) {
+        if container_attrs.take {
+            if attrs.each.is_some() {
+                return quote! {
+                    #field_name: std::mem::take(&mut self.#field_name)
+                };
+            }
+            if unwrap_wrapper_t("Option", ty).is_some() {
+                return quote! {
+                    #field_name: self.#field_name.take()
+                };
+            }
+            return quote! {
+                #field_name: self.#field_name.take().ok_or(concat!(stringify!(#field_name), " is not set"))?
+            };
+        }
+        if unwrap_wrapper_t("Option", ty).is_some() || attrs.each.is_some() {
             let expr = quote! {
                 #field_name: self.#field_name.clone()
             };
@@ -74,29 +126,47 @@ pub fn derive(input: TokenStream) -> TokenStream {
             #field_name: self.#field_name.clone().ok_or(concat!(stringify!(#field_name), " is not set"))?
         }
     });
-    let build_empty = fields.iter().map(|f| {
+    // In take mode, check every required field up front so that a failed
+    // build does not leave the builder half emptied.
+    let build_checks = fields.iter().filter(|(f, attrs)| {
+        container_attrs.take && unwrap_wrapper_t("Option", &f.ty).is_none() && attrs.each.is_none()
+    }).map(|(f, _)| {
+        let field_name = &f.ident;
+        quote! {
+            if self.#field_name.is_none() {
+                return std::result::Result::Err(std::convert::Into::into(concat!(stringify!(#field_name), " is not set")));
+            }
+        }
+    });
+    let build_receiver = if container_attrs.take {
+        quote! { &mut self }
+    } else {
+        quote! { &self }
+    };
+    let build_empty = fields.iter().map(|(f, attrs)| {
         let field_name = &f.ident;
-        if builder_of(&f) {
+        if attrs.each.is_some() {
             quote! { #field_name: std::vec::Vec::new() }
         } else {
             quote! { #field_name: std::option::Option::None }
         }
     });
     let expanded = quote! {
-        struct #builder_ident {
+        struct #builder_ident #impl_generics #where_clause {
             #(#fields_after_option_types,)*
         }
-        impl #builder_ident {
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
             #(#methods)*
 
-            fn build(&self) -> std::result::Result<#name, std::boxed::Box<dyn std::error::Error>> {
+            fn build(#build_receiver) -> std::result::Result<#name #ty_generics, std::boxed::Box<dyn std::error::Error>> {
+                #(#build_checks)*
                 std::result::Result::Ok (#name {
                     #(#build_method,)*
                 })
             }
         }
-        impl #name {
-            fn builder() -> #builder_ident {
+        impl #impl_generics #name #ty_generics #where_clause {
+            fn builder() -> #builder_ident #ty_generics {
                 #builder_ident {
                     #(#build_empty,)*
                 }
@@ -124,61 +194,154 @@ fn unwrap_wrapper_t<'a>(wrapper_t: &'a str, ty: &'a syn::Type) -> Option<&'a syn
     None
 }
 
-fn builder_of(f: &syn::Field) -> bool {
-    for attr in &f.attrs {
-        if attr.path().is_ident("builder") {
-            return true;
+/// Options collected from the `#[builder(...)]` attributes on the struct itself.
+#[derive(Default)]
+struct ContainerAttrs {
+    /// `build_fn(take)`: move values out of the builder instead of cloning.
+    take: bool,
+}
+
+fn parse_container_attrs(attrs: &[syn::Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut container_attrs = ContainerAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
         }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("build_fn") {
+                meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("take") {
+                        if container_attrs.take {
+                            return Err(syn::Error::new_spanned(&attr.meta, "duplicate `take` in builder attributes"));
+                        }
+                        container_attrs.take = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `build_fn(take)`"))
+                    }
+                })
+            } else {
+                Err(syn::Error::new_spanned(&attr.meta, "expected `builder(build_fn(take))`"))
+            }
+        })?;
     }
-    false 
+
+    Ok(container_attrs)
 }
 
-fn extended_methods(f: &syn::Field) -> Option<(bool, proc_macro2::TokenStream)> {
-    let field_name = &f.ident;
-    let mut avoid_conflict = false;
+/// Options collected from every `#[builder(...)]` attribute on one field.
+#[derive(Default)]
+struct FieldAttrs {
+    /// The one-at-a-time setter name, together with the attribute it came
+    /// from so that later checks can point at it.
+    each: Option<(syn::Ident, syn::Meta)>,
+}
 
-    for attr in &f.attrs {
-        if attr.path().is_ident("builder") {
-            let mut lit = None;
-
-            let result = attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident("each") {
-                    lit = Some(meta.value()?.parse::<syn::LitStr>()?);
-                    Ok(())
-                } else {
-                    Err(meta.error("expected `builder(each = \"...\")`"))
-                }
-            });
+/// Parses the builder attributes of every field, then checks the struct as a
+/// whole for setters whose names would collide. All errors are combined so
+/// the caller sees every problem at once.
+fn parse_all_field_attrs(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+) -> syn::Result<Vec<FieldAttrs>> {
+    let mut errors: Option<syn::Error> = None;
+    let mut push_error = |err: syn::Error| match errors {
+        Some(ref mut errors) => errors.combine(err),
+        None => errors = Some(err),
+    };
 
-            if let Err(err) = result {
-                return Some((false, err.to_compile_error()));
+    let mut all_attrs = Vec::new();
+    for f in fields {
+        match parse_field_attrs(f) {
+            Ok(attrs) => all_attrs.push(attrs),
+            Err(err) => {
+                push_error(err);
+                all_attrs.push(FieldAttrs::default());
             }
+        }
+    }
 
-            if let Some(lit) = lit {
-                let extend_fn_name = syn::Ident::new(&lit.value(), lit.span());
+    // Every field gets a setter named after itself unless its `each` setter
+    // takes over that name.
+    let mut setters: Vec<&syn::Ident> = fields.iter().filter_map(|f| f.ident.as_ref()).collect();
+    for (f, attrs) in fields.iter().zip(&all_attrs) {
+        let Some((each, meta)) = &attrs.each else {
+            continue;
+        };
+        if f.ident.as_ref() == Some(each) {
+            continue;
+        }
+        if each == "build" {
+            push_error(syn::Error::new_spanned(
+                meta,
+                "`each` setter `build` conflicts with the generated `build` method",
+            ));
+        } else if setters.contains(&each) {
+            push_error(syn::Error::new_spanned(
+                meta,
+                format!("`each` setter `{}` conflicts with another setter of the same name", each),
+            ));
+        } else {
+            setters.push(each);
+        }
+    }
 
-                if field_name.as_ref() == Some(&extend_fn_name) {
-                    avoid_conflict = true;
-                }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(all_attrs),
+    }
+}
 
-                let inner_ty = unwrap_wrapper_t("Vec", &f.ty).unwrap_or_else(|| {
-                    panic!(
-                        "Field with `builder(each = ...)` must be of type `Vec`. Field: {:?}",
-                        field_name
-                    );
-                });
-
-                let expanded = quote! {
-                    fn #extend_fn_name(&mut self, #extend_fn_name: #inner_ty) -> &mut Self {
-                        self.#field_name.push(#extend_fn_name);
-                        self
-                    }
-                };
+fn parse_field_attrs(f: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+
+    for attr in &f.attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("each") {
+                let lit = meta.value()?.parse::<syn::LitStr>()?;
+                if attrs.each.is_some() {
+                    return Err(syn::Error::new_spanned(&attr.meta, "duplicate `each` in builder attributes"));
+                }
+                attrs.each = Some((lit.parse::<syn::Ident>()?, attr.meta.clone()));
+                Ok(())
+            } else {
+                Err(syn::Error::new_spanned(&attr.meta, "expected `builder(each = \"...\")`"))
+            }
+        })?;
+    }
 
-                return Some((avoid_conflict, expanded));
+    if let Some((_, meta)) = &attrs.each {
+        if let Some(inner_ty) = unwrap_wrapper_t("Option", &f.ty) {
+            if unwrap_wrapper_t("Vec", inner_ty).is_some() {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "`each` cannot be used on `Option<Vec<T>>`; use `Vec<T>`, which defaults to empty",
+                ));
             }
         }
+        if unwrap_wrapper_t("Vec", &f.ty).is_none() {
+            return Err(syn::Error::new_spanned(meta, "`each` can only be used on fields of type `Vec<T>`"));
+        }
     }
 
-    None
+    Ok(attrs)
+}
+
+fn extended_method(f: &syn::Field, attrs: &FieldAttrs) -> Option<(bool, proc_macro2::TokenStream)> {
+    let field_name = &f.ident;
+    let (extend_fn_name, _) = attrs.each.as_ref()?;
+    let avoid_conflict = field_name.as_ref() == Some(extend_fn_name);
+    let inner_ty = unwrap_wrapper_t("Vec", &f.ty)?;
+
+    let expanded = quote! {
+        fn #extend_fn_name(&mut self, #extend_fn_name: #inner_ty) -> &mut Self {
+            self.#field_name.push(#extend_fn_name);
+            self
+        }
+    };
+
+    Some((avoid_conflict, expanded))
 }
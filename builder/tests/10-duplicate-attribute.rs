@@ -0,0 +1,18 @@
+// A field may carry at most one `each` key, whether it is repeated inside a
+// single #[builder(...)] attribute or spread across several of them. The error
+// should point at the attribute that introduces the duplicate rather than at
+// some conflicting method deep inside the generated code.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    #[builder(each = "argument")]
+    args: Vec<String>,
+    #[builder(each = "env", each = "var")]
+    env: Vec<String>,
+}
+
+fn main() {}
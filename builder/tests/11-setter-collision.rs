@@ -0,0 +1,24 @@
+// The one-at-a-time setter named by `each` lives in the same namespace as the
+// setters generated for every other field, and as the builder's own `build`
+// method. Report a name that is already taken at the attribute that asks for
+// it.
+//
+// Giving the `each` setter the same name as its own field is still fine; that
+// case is covered by 07-repeated-field.rs.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "executable")]
+    args: Vec<String>,
+    #[builder(each = "var")]
+    env: Vec<String>,
+    #[builder(each = "var")]
+    env_remove: Vec<String>,
+    #[builder(each = "build")]
+    features: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+// The `each` setter pushes onto a Vec that starts out empty, so it only makes
+// sense on fields of type Vec<T>. An Option<Vec<T>> field would have no way to
+// tell "never set" apart from "set to empty", and any other type has nothing
+// to push onto.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Option<Vec<String>>,
+    #[builder(each = "dir")]
+    current_dir: String,
+}
+
+fn main() {}
@@ -0,0 +1,58 @@
+// The default `build(&self)` clones every field out of the builder, which
+// rules out fields that are not Clone: mutable references, open files, boxed
+// trait objects and so on.
+//
+// With #[builder(build_fn(take))] on the struct, generate
+//
+//     impl<'a> WriterBuilder<'a> {
+//         fn build(&mut self) -> Result<Writer<'a>, Box<dyn Error>> {
+//             ...
+//         }
+//     }
+//
+// which moves each value out with Option::take (or mem::take for `each`
+// fields) and so leaves the builder empty for reuse. Every required field is
+// checked before anything is moved, so a failed build does not lose the values
+// that were already set.
+
+use derive_builder::Builder;
+use std::fs::File;
+use std::io::Write;
+
+#[derive(Builder)]
+#[builder(build_fn(take))]
+pub struct Writer<'a> {
+    log: File,
+    sink: Box<dyn Write>,
+    scratch: &'a mut Vec<u8>,
+    #[builder(each = "header")]
+    headers: Vec<String>,
+    label: Option<String>,
+}
+
+fn main() {
+    let mut scratch = Vec::new();
+    let mut builder = Writer::builder();
+    builder
+        .sink(Box::new(std::io::sink()))
+        .scratch(&mut scratch)
+        .header("a".to_owned())
+        .label("out".to_owned());
+
+    // `log` is missing; nothing else should have been taken.
+    assert!(builder.build().is_err());
+
+    builder.log(File::open("Cargo.toml").unwrap());
+    let mut writer = builder.build().unwrap();
+    writer.sink.write_all(b"hello").unwrap();
+    writer.scratch.push(1);
+    assert_eq!(writer.headers, ["a"]);
+    assert_eq!(writer.label.as_deref(), Some("out"));
+    let _ = writer.log.metadata().unwrap();
+
+    // The builder has been reset by the successful build.
+    let err = builder.build().err().unwrap();
+    assert_eq!(err.to_string(), "log is not set");
+
+    assert_eq!(scratch, [1]);
+}
@@ -0,0 +1,13 @@
+// A `&mut` field can never be cloned out of the builder. Rather than letting
+// the generated `build(&self)` fail to compile somewhere inside the macro
+// output, point at the field and suggest #[builder(build_fn(take))].
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Writer<'a> {
+    name: String,
+    scratch: &'a mut Vec<u8>,
+}
+
+fn main() {}
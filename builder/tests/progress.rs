@@ -1,13 +1,18 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    // t.pass("tests/01-parse.rs");
-    // t.pass("tests/02-create-builder.rs");
-    // t.pass("tests/03-call-setters.rs");
-    // t.pass("tests/04-call-build.rs");
-    // t.pass("tests/05-method-chaining.rs");
-    // t.pass("tests/06-optional-field.rs");
-    // t.pass("tests/07-repeated-field.rs");
-    //t.compile_fail("tests/08-unrecognized-attribute.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-create-builder.rs");
+    t.pass("tests/03-call-setters.rs");
+    t.pass("tests/04-call-build.rs");
+    t.pass("tests/05-method-chaining.rs");
+    t.pass("tests/06-optional-field.rs");
+    t.pass("tests/07-repeated-field.rs");
+    t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.compile_fail("tests/10-duplicate-attribute.rs");
+    t.compile_fail("tests/11-setter-collision.rs");
+    t.compile_fail("tests/12-invalid-each-type.rs");
+    t.pass("tests/13-build-fn-take.rs");
+    t.compile_fail("tests/14-mut-ref-without-take.rs");
 }
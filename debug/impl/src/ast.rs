@@ -0,0 +1,116 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use crate::attr::{self, FieldAttrs, VariantAttrs};
+use crate::template::Template;
+
+/// One struct body or enum variant, with its attributes already parsed.
+pub(crate) struct Variant<'a> {
+    /// `None` for the body of a struct.
+    pub ident: Option<&'a syn::Ident>,
+    /// The name printed in the output.
+    pub name: String,
+    pub style: Style,
+    pub fields: Vec<Field<'a>>,
+    /// Formats the whole value instead of listing its fields.
+    pub template: Option<Template>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Style {
+    Named,
+    Unnamed,
+    Unit,
+}
+
+pub(crate) struct Field<'a> {
+    pub member: syn::Member,
+    /// The name printed in the output for named fields, after any rename.
+    pub name: String,
+    /// The local that holds a reference to this field inside a match arm.
+    pub binding: syn::Ident,
+    pub ty: &'a syn::Type,
+    pub attrs: FieldAttrs,
+}
+
+impl<'a> Variant<'a> {
+    pub fn from_struct(ident: &'a syn::Ident, fields: &'a syn::Fields, template: Option<Template>) -> syn::Result<Self> {
+        Ok(Variant {
+            ident: None,
+            name: ident.unraw().to_string(),
+            style: Style::of(fields),
+            fields: Field::multiple_from_syn(fields)?,
+            template,
+        })
+    }
+
+    pub fn from_enum(variant: &'a syn::Variant) -> syn::Result<Self> {
+        let VariantAttrs { rename, template } = attr::parse_variant_attrs(&variant.attrs)?;
+        Ok(Variant {
+            ident: Some(&variant.ident),
+            name: rename.map_or_else(|| variant.ident.unraw().to_string(), |rename| rename.value()),
+            style: Style::of(&variant.fields),
+            fields: Field::multiple_from_syn(&variant.fields)?,
+            template,
+        })
+    }
+
+    /// A pattern matching `self` that binds the fields selected by `bind` by
+    /// reference. The others are matched with `_`, since they would otherwise
+    /// be reported as unused.
+    pub fn pattern(&self, bind: impl Fn(&Field) -> bool) -> TokenStream {
+        self.pattern_with(|f| if bind(f) { Some(f.binding.clone()) } else { None })
+    }
+
+    /// Like `pattern`, but binds each field to the local returned by
+    /// `binding`, for when two values are matched side by side.
+    pub fn pattern_with(&self, binding: impl Fn(&Field) -> Option<syn::Ident>) -> TokenStream {
+        let path = match self.ident {
+            Some(ident) => quote!(Self::#ident),
+            None => quote!(Self),
+        };
+        let members = self.fields.iter().map(|f| &f.member);
+        let bindings = self.fields.iter().map(|f| match binding(f) {
+            Some(binding) => quote!(#binding),
+            None => quote!(_),
+        });
+        match self.style {
+            Style::Unit => path,
+            _ => quote!(#path { #(#members: #bindings),* }),
+        }
+    }
+}
+
+impl Style {
+    fn of(fields: &syn::Fields) -> Self {
+        match fields {
+            syn::Fields::Named(_) => Style::Named,
+            syn::Fields::Unnamed(_) => Style::Unnamed,
+            syn::Fields::Unit => Style::Unit,
+        }
+    }
+}
+
+impl<'a> Field<'a> {
+    fn multiple_from_syn(fields: &'a syn::Fields) -> syn::Result<Vec<Self>> {
+        fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let (member, name) = match &f.ident {
+                    Some(ident) => (syn::Member::Named(ident.clone()), ident.unraw().to_string()),
+                    None => (syn::Member::Unnamed(i.into()), i.to_string()),
+                };
+                let attrs = attr::parse_field_attrs(&f.attrs)?;
+                Ok(Field {
+                    member,
+                    name: attrs.rename.as_ref().map_or(name, syn::LitStr::value),
+                    binding: format_ident!("__self_{}", i),
+                    ty: &f.ty,
+                    attrs,
+                })
+            })
+            .collect()
+    }
+}
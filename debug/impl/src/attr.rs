@@ -0,0 +1,502 @@
+use syn::spanned::Spanned;
+
+use crate::template::Template;
+
+pub(crate) type Predicates = syn::punctuated::Punctuated<syn::WherePredicate, syn::Token![,]>;
+
+/// Options collected from the `#[debug(...)]` attributes on the type itself.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// `#[debug(bound = "...")]`: added to the bounds of the chosen `infer`
+    /// strategy, and turns inference off if there is none.
+    pub bound: Option<Predicates>,
+    /// `#[debug(bound(infer = "..."))]`: which type parameters get bounds.
+    pub infer: Option<InferBounds>,
+    /// `#[debug(transparent)]`: format a single-field struct as its field.
+    pub transparent: Option<syn::Path>,
+    /// `#[debug(redact_all_except(a, b))]`: redact every other named field.
+    pub redact_all_except: Option<Vec<syn::Ident>>,
+    /// `#[debug("Point({x}, {y})")]`: format the whole struct from a template.
+    pub template: Option<Template>,
+    /// `#[debug(format = "logfmt")]` and `#[debug(format = "json")]`: also
+    /// generate `debug_logfmt` and `debug_json` methods.
+    pub formats: Vec<StructuredFormat>,
+    /// `#[debug(compact)]`: print on one line even under `{:#?}`.
+    pub compact: Option<syn::Path>,
+    /// `#[debug(skip_none)]`: leave out every `Option` field that is `None`.
+    pub skip_none: Option<syn::Path>,
+    /// `#[debug(enum_discriminant)]` or `#[debug(enum_discriminant = "...")]`:
+    /// print each variant's discriminant after its name.
+    pub enum_discriminant: Option<EnumDiscriminant>,
+    /// `#[debug(with = path)]`: a `fn(&Self, &mut Formatter) -> fmt::Result`
+    /// that formats the whole value.
+    pub with: Option<syn::Path>,
+    /// `#[debug(opaque)]` or `#[debug(opaque(address))]`: print only the type
+    /// name, and optionally where the value lives.
+    pub opaque: Option<Opaque>,
+}
+
+pub(crate) struct Opaque {
+    pub path: syn::Path,
+    pub address: bool,
+}
+
+pub(crate) struct EnumDiscriminant {
+    pub path: syn::Path,
+    pub radix: Radix,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Radix {
+    Hex,
+    Binary,
+    Decimal,
+}
+
+/// The strategy chosen with `#[debug(bound(infer = "..."))]`.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum InferBounds {
+    /// `"all"`: bound every type parameter, used or not, as well as the
+    /// associated types the printed fields use.
+    All,
+    /// `"used"`: bound what the printed fields use outside `PhantomData`.
+    Used,
+    /// `"none"`: bound nothing, leaving it all to `bound = "..."`.
+    None,
+}
+
+impl ContainerAttrs {
+    /// The inference strategy to use. Without one given explicitly, a
+    /// container-level `bound = "..."` turns inference off.
+    pub fn infer_bounds(&self) -> InferBounds {
+        match (self.infer, &self.bound) {
+            (Some(infer), _) => infer,
+            (None, Some(_)) => InferBounds::None,
+            (None, None) => InferBounds::Used,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum StructuredFormat {
+    Logfmt,
+    Json,
+}
+
+pub(crate) fn parse_container_attrs(attrs: &[syn::Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut container_attrs = ContainerAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("debug") {
+            continue;
+        }
+        if let Some(template) = parse_template(attr) {
+            set_once(&mut container_attrs.template, template?, attr)?;
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("bound") && meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|meta| {
+                    if !meta.path.is_ident("infer") {
+                        return Err(meta.error("expected `bound(infer = \"...\")`"));
+                    }
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    let infer = match lit.value().as_str() {
+                        "all" => InferBounds::All,
+                        "used" => InferBounds::Used,
+                        "none" => InferBounds::None,
+                        _ => return Err(syn::Error::new_spanned(lit, "expected `\"all\"`, `\"used\"` or `\"none\"`")),
+                    };
+                    set_once(&mut container_attrs.infer, infer, attr)
+                })
+            } else if meta.path.is_ident("bound") {
+                let bound = parse_bound_value(&meta)?;
+                set_once(&mut container_attrs.bound, bound, attr)
+            } else if meta.path.is_ident("transparent") {
+                set_once(&mut container_attrs.transparent, meta.path, attr)
+            } else if meta.path.is_ident("compact") {
+                set_once(&mut container_attrs.compact, meta.path, attr)
+            } else if meta.path.is_ident("skip_none") {
+                set_once(&mut container_attrs.skip_none, meta.path, attr)
+            } else if meta.path.is_ident("enum_discriminant") {
+                let radix = if meta.input.peek(syn::Token![=]) {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    match lit.value().as_str() {
+                        "hex" => Radix::Hex,
+                        "binary" => Radix::Binary,
+                        "decimal" => Radix::Decimal,
+                        _ => return Err(syn::Error::new_spanned(lit, "expected `\"hex\"`, `\"binary\"` or `\"decimal\"`")),
+                    }
+                } else {
+                    Radix::Hex
+                };
+                set_once(&mut container_attrs.enum_discriminant, EnumDiscriminant { path: meta.path, radix }, attr)
+            } else if meta.path.is_ident("with") {
+                let path = parse_path_value(&meta)?;
+                set_once(&mut container_attrs.with, path, attr)
+            } else if meta.path.is_ident("opaque") {
+                let mut address = false;
+                if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("address") {
+                            address = true;
+                            Ok(())
+                        } else {
+                            Err(meta.error("expected `opaque(address)`"))
+                        }
+                    })?;
+                }
+                set_once(&mut container_attrs.opaque, Opaque { path: meta.path, address }, attr)
+            } else if meta.path.is_ident("redact_all_except") {
+                let mut except = Vec::new();
+                meta.parse_nested_meta(|meta| match meta.path.get_ident() {
+                    Some(ident) => {
+                        except.push(ident.clone());
+                        Ok(())
+                    }
+                    None => Err(meta.error("expected a field name")),
+                })?;
+                set_once(&mut container_attrs.redact_all_except, except, attr)
+            } else if meta.path.is_ident("format") {
+                let lit = meta.value()?.parse::<syn::LitStr>()?;
+                let format = match lit.value().as_str() {
+                    "logfmt" => StructuredFormat::Logfmt,
+                    "json" => StructuredFormat::Json,
+                    _ => return Err(syn::Error::new_spanned(lit, "expected `format = \"logfmt\"` or `format = \"json\"`")),
+                };
+                if container_attrs.formats.contains(&format) {
+                    return Err(syn::Error::new_spanned(lit, "duplicate format"));
+                }
+                container_attrs.formats.push(format);
+                Ok(())
+            } else {
+                Err(meta.error("unrecognized debug attribute"))
+            }
+        })?;
+    }
+
+    check_whole_value(&container_attrs)?;
+    Ok(container_attrs)
+}
+
+/// `with` and `opaque` replace the field list entirely, so they make no
+/// sense together or with the options that shape that list.
+fn check_whole_value(container_attrs: &ContainerAttrs) -> syn::Result<()> {
+    let whole = [
+        container_attrs.with.as_ref().map(|path| ("`with`", path)),
+        container_attrs.opaque.as_ref().map(|opaque| ("`opaque`", &opaque.path)),
+    ];
+    let mut whole = whole.into_iter().flatten();
+    let Some((name, path)) = whole.next() else {
+        return Ok(());
+    };
+    if let Some((second, path)) = whole.next() {
+        return Err(syn::Error::new_spanned(path, format!("{} cannot be combined with {}", second, name)));
+    }
+    let others = [
+        container_attrs.transparent.as_ref().map(|_| "`transparent`"),
+        container_attrs.redact_all_except.as_ref().map(|_| "`redact_all_except`"),
+        container_attrs.template.as_ref().map(|_| "a template"),
+    ];
+    if let Some(other) = others.into_iter().flatten().next() {
+        return Err(syn::Error::new_spanned(path, format!("{} cannot be combined with {}", name, other)));
+    }
+    Ok(())
+}
+
+/// Options collected from the `#[debug(...)]` attributes on one enum variant.
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    /// `#[debug(rename = "...")]`: the name printed instead of the variant's.
+    pub rename: Option<syn::LitStr>,
+    /// `#[debug("...")]`: format the whole variant from a template.
+    pub template: Option<Template>,
+}
+
+pub(crate) fn parse_variant_attrs(attrs: &[syn::Attribute]) -> syn::Result<VariantAttrs> {
+    let mut variant_attrs = VariantAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("debug") {
+            continue;
+        }
+        if let Some(template) = parse_template(attr) {
+            set_once(&mut variant_attrs.template, template?, attr)?;
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let rename = meta.value()?.parse::<syn::LitStr>()?;
+                set_once(&mut variant_attrs.rename, rename, attr)
+            } else {
+                Err(meta.error("unrecognized debug attribute"))
+            }
+        })?;
+    }
+
+    Ok(variant_attrs)
+}
+
+/// Options collected from every `#[debug ...]` attribute on one field.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// `#[debug = "..."]`: a format string with exactly one placeholder.
+    pub format: Option<syn::LitStr>,
+    /// `#[debug(with = path)]`: a `fn(&T, &mut Formatter) -> fmt::Result`.
+    pub with: Option<syn::Path>,
+    /// `#[debug(bound = "...")]`: replaces the bounds inferred from this field.
+    pub bound: Option<Predicates>,
+    /// `#[debug(skip)]`: leave the field out of the output.
+    pub skip: Option<syn::Path>,
+    /// `#[debug(skip_if = path)]`: leave the field out whenever a
+    /// `fn(&T) -> bool` returns true for it.
+    pub skip_if: Option<syn::Path>,
+    /// `#[debug(redact)]` or `#[debug(redact = "len")]`.
+    pub redact: Option<Redact>,
+    /// `#[debug(truncate = N)]`: print at most N elements or characters.
+    pub truncate: Option<syn::LitInt>,
+    /// `#[debug(hex)]`: print bytes as a hex dump.
+    pub hex: Option<syn::Path>,
+    /// `#[debug(rename = "...")]`: the name printed instead of the field's.
+    pub rename: Option<syn::LitStr>,
+    /// `#[debug(flatten)]`: print the fields of a struct that also derives
+    /// CustomDebug as if they were fields of this one.
+    pub flatten: Option<syn::Path>,
+}
+
+impl FieldAttrs {
+    /// Whether the field is printed through its own Debug impl, and so needs
+    /// a Debug bound.
+    pub fn uses_debug(&self) -> bool {
+        self.format.is_none()
+            && self.with.is_none()
+            && self.skip.is_none()
+            && self.redact.is_none()
+            && self.hex.is_none()
+            && self.flatten.is_none()
+    }
+
+    /// Whether the field is printed as a plain `<redacted>`, revealing
+    /// nothing about its value.
+    pub fn is_redacted(&self) -> bool {
+        self.redact.as_ref().is_some_and(|redact| !redact.len)
+    }
+}
+
+pub(crate) struct Redact {
+    pub path: syn::Path,
+    /// Print the value's `len()` instead of nothing at all.
+    pub len: bool,
+}
+
+pub(crate) fn parse_field_attrs(attrs: &[syn::Attribute]) -> syn::Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("debug") {
+            continue;
+        }
+        if let syn::Meta::NameValue(nv) = &attr.meta {
+            let lit = match &nv.value {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => lit,
+                other => return Err(syn::Error::new_spanned(other, "expected a format string like `#[debug = \"{:?}\"]`")),
+            };
+            check_format_string(lit)?;
+            set_once(&mut field_attrs.format, lit.clone(), attr)?;
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("with") {
+                let path = parse_path_value(&meta)?;
+                set_once(&mut field_attrs.with, path, attr)
+            } else if meta.path.is_ident("bound") {
+                let bound = parse_bound_value(&meta)?;
+                set_once(&mut field_attrs.bound, bound, attr)
+            } else if meta.path.is_ident("skip") {
+                set_once(&mut field_attrs.skip, meta.path, attr)
+            } else if meta.path.is_ident("skip_if") {
+                let path = parse_path_value(&meta)?;
+                set_once(&mut field_attrs.skip_if, path, attr)
+            } else if meta.path.is_ident("redact") {
+                let len = if meta.input.peek(syn::Token![=]) {
+                    let mode = meta.value()?.parse::<syn::LitStr>()?;
+                    if mode.value() != "len" {
+                        return Err(syn::Error::new(mode.span(), "expected `redact` or `redact = \"len\"`"));
+                    }
+                    true
+                } else {
+                    false
+                };
+                set_once(&mut field_attrs.redact, Redact { path: meta.path, len }, attr)
+            } else if meta.path.is_ident("truncate") {
+                let limit = meta.value()?.parse::<syn::LitInt>()?;
+                if limit.base10_parse::<usize>()? == 0 {
+                    return Err(syn::Error::new(limit.span(), "`truncate` needs a limit of at least 1"));
+                }
+                set_once(&mut field_attrs.truncate, limit, attr)
+            } else if meta.path.is_ident("hex") {
+                set_once(&mut field_attrs.hex, meta.path, attr)
+            } else if meta.path.is_ident("rename") {
+                let rename = meta.value()?.parse::<syn::LitStr>()?;
+                set_once(&mut field_attrs.rename, rename, attr)
+            } else if meta.path.is_ident("flatten") {
+                set_once(&mut field_attrs.flatten, meta.path, attr)
+            } else {
+                Err(meta.error("unrecognized debug attribute"))
+            }
+        })?;
+    }
+
+    // Each of these decides on its own how the field is printed, so at most
+    // one of them may be given. `truncate` and `hex` work together.
+    let exclusive = [
+        field_attrs.format.as_ref().map(|lit| ("a `#[debug = \"...\"]` format string", lit.span())),
+        field_attrs.with.as_ref().map(|path| ("`with`", path.span())),
+        field_attrs.skip.as_ref().map(|path| ("`skip`", path.span())),
+        field_attrs.redact.as_ref().map(|redact| ("`redact`", redact.path.span())),
+        field_attrs.truncate.as_ref().map(|limit| ("`truncate`", limit.span())).filter(|_| field_attrs.hex.is_none()),
+        field_attrs.hex.as_ref().map(|path| ("`hex`", path.span())),
+        field_attrs.flatten.as_ref().map(|path| ("`flatten`", path.span())),
+    ];
+    let mut exclusive = exclusive.into_iter().flatten();
+    if let (Some((first, _)), Some((second, span))) = (exclusive.next(), exclusive.next()) {
+        return Err(syn::Error::new(span, format!("{} cannot be combined with {}", second, first)));
+    }
+    if let (Some(_), Some(skip_if)) = (&field_attrs.skip, &field_attrs.skip_if) {
+        return Err(syn::Error::new_spanned(skip_if, "`skip_if` cannot be combined with `skip`"));
+    }
+    if let (Some(flatten), Some(rename)) = (&field_attrs.flatten, &field_attrs.rename) {
+        return Err(syn::Error::new(
+            rename.span().join(flatten.span()).unwrap_or_else(|| rename.span()),
+            "`rename` cannot be combined with `flatten`, which prints no name for the field",
+        ));
+    }
+
+    Ok(field_attrs)
+}
+
+/// Options collected from the `#[display(...)]` attributes on a type or on one
+/// enum variant, for `CustomDisplay`.
+#[derive(Default)]
+pub(crate) struct DisplayAttrs {
+    /// `#[display("...")]`: the template to print.
+    pub template: Option<Template>,
+    /// `#[display(transparent)]`: forward to the only field's Display impl.
+    pub transparent: Option<syn::Path>,
+    /// `#[display(bound = "...")]`: replaces all inferred bounds.
+    pub bound: Option<Predicates>,
+}
+
+pub(crate) fn parse_display_attrs(attrs: &[syn::Attribute]) -> syn::Result<DisplayAttrs> {
+    let mut display_attrs = DisplayAttrs::default();
+
+    for attr in attrs {
+        if !attr.path().is_ident("display") {
+            continue;
+        }
+        if let Some(template) = parse_template(attr) {
+            set_once(&mut display_attrs.template, template?, attr)?;
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("transparent") {
+                set_once(&mut display_attrs.transparent, meta.path, attr)
+            } else if meta.path.is_ident("bound") {
+                let bound = parse_bound_value(&meta)?;
+                set_once(&mut display_attrs.bound, bound, attr)
+            } else {
+                Err(meta.error("unrecognized display attribute"))
+            }
+        })?;
+    }
+
+    if let (Some(_), Some(transparent)) = (&display_attrs.template, &display_attrs.transparent) {
+        return Err(syn::Error::new_spanned(transparent, "`transparent` cannot be combined with a template"));
+    }
+
+    Ok(display_attrs)
+}
+
+/// Stores `value` in `slot`, reporting a duplicate at `attr` if the slot is
+/// already filled.
+fn set_once<T>(slot: &mut Option<T>, value: T, attr: &syn::Attribute) -> syn::Result<()> {
+    if slot.is_some() {
+        let name = attr.path().get_ident().map_or_else(String::new, ToString::to_string);
+        return Err(syn::Error::new_spanned(&attr.meta, format!("duplicate {} attribute", name)));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Parses `#[debug("...")]`, returning `None` if the attribute does not
+/// start with a string literal.
+fn parse_template(attr: &syn::Attribute) -> Option<syn::Result<Template>> {
+    let syn::Meta::List(list) = &attr.meta else {
+        return None;
+    };
+    let lit = syn::parse2::<syn::LitStr>(list.tokens.clone()).ok()?;
+    Some(Template::parse(&lit))
+}
+
+/// Accepts both `key = path::to::item` and `key = "path::to::item"`.
+fn parse_path_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<syn::Path> {
+    let value = meta.value()?;
+    if value.peek(syn::LitStr) {
+        value.parse::<syn::LitStr>()?.parse()
+    } else {
+        value.parse()
+    }
+}
+
+/// Parses `bound = "T: Trait, U::Item: Debug"` into where predicates.
+fn parse_bound_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Predicates> {
+    let lit = meta.value()?.parse::<syn::LitStr>()?;
+    lit.parse_with(Predicates::parse_terminated)
+        .map_err(|err| syn::Error::new(lit.span(), format!("invalid where predicates in bound: {}", err)))
+}
+
+/// Rejects field format strings that would make `format_args!` fail inside
+/// the generated code, so that the error lands on the attribute instead.
+fn check_format_string(lit: &syn::LitStr) -> syn::Result<()> {
+    let value = lit.value();
+    let mut chars = value.chars().peekable();
+    let mut placeholders = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '{' => {
+                let mut arg = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err(syn::Error::new(lit.span(), "unterminated `{` in format string")),
+                    }
+                }
+                let position = arg.split(':').next().unwrap_or_default();
+                if !position.is_empty() && position != "0" {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        format!("`{{{}}}` does not refer to the field; use `{{}}` or `{{:...}}`", arg),
+                    ));
+                }
+                placeholders += 1;
+            }
+            '}' => return Err(syn::Error::new(lit.span(), "unmatched `}` in format string; use `}}` for a literal brace")),
+            _ => {}
+        }
+    }
+    if placeholders != 1 {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!("format string must contain exactly one `{{}}` placeholder for the field, found {}", placeholders),
+        ));
+    }
+    Ok(())
+}
+
@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+
+use quote::{quote, ToTokens};
+
+/// Collects the trait bounds needed by the generated impl.
+///
+/// Each type parameter that appears in a field type gets `T: Debug` (or
+/// whichever trait the field is formatted through), unless it
+/// only appears inside `PhantomData<...>`. Associated types such as
+/// `T::Value` or `<T as Trait>::Value` are bounded directly instead of
+/// bounding `T`, at any depth inside the field type.
+pub(crate) struct BoundInference<'a> {
+    params: BTreeSet<&'a syn::Ident>,
+    /// The trait being inferred for the field currently being walked.
+    bound: syn::Path,
+    predicates: Vec<syn::WherePredicate>,
+    seen: BTreeSet<String>,
+}
+
+impl<'a> BoundInference<'a> {
+    pub fn new(generics: &'a syn::Generics) -> Self {
+        BoundInference {
+            params: generics.type_params().map(|param| &param.ident).collect(),
+            bound: syn::parse_quote!(std::fmt::Debug),
+            predicates: Vec::new(),
+            seen: BTreeSet::new(),
+        }
+    }
+
+    /// Bounds every type parameter on `bound`, whether or not any field
+    /// uses it.
+    pub fn all_params(&mut self, bound: syn::Path) {
+        let params: Vec<&syn::Ident> = self.params.iter().copied().collect();
+        self.extend(params.into_iter().map(|param| syn::parse_quote!(#param: #bound)));
+    }
+
+    /// Adds the bounds inferred from one field's type, where `bound` is the
+    /// trait the field is formatted through: usually `Debug`, but templates
+    /// may use `Display`, `LowerHex` and so on.
+    pub fn infer(&mut self, ty: &syn::Type, bound: syn::Path) {
+        self.bound = bound;
+        self.walk(ty);
+    }
+
+    fn walk(&mut self, ty: &syn::Type) {
+        match ty {
+            syn::Type::Path(p) => self.infer_path(p),
+            syn::Type::Reference(r) => self.walk(&r.elem),
+            syn::Type::Ptr(p) => self.walk(&p.elem),
+            syn::Type::Slice(s) => self.walk(&s.elem),
+            syn::Type::Array(a) => self.walk(&a.elem),
+            syn::Type::Paren(p) => self.walk(&p.elem),
+            syn::Type::Group(g) => self.walk(&g.elem),
+            syn::Type::Tuple(t) => {
+                for elem in &t.elems {
+                    self.walk(elem);
+                }
+            }
+            // Function pointers are Debug regardless of their signature, and
+            // trait objects and macros can't be bounded in a useful way.
+            _ => {}
+        }
+    }
+
+    fn infer_path(&mut self, p: &syn::TypePath) {
+        if let Some(qself) = &p.qself {
+            // `<T as Trait>::Value`
+            if self.mentions_param(&qself.ty) {
+                self.push_bound(p);
+            }
+            return;
+        }
+        let segments = &p.path.segments;
+        if p.path.leading_colon.is_none() && self.params.contains(&segments[0].ident) {
+            // `T` or `T::Value`
+            self.push_bound(p);
+            return;
+        }
+        if segments.last().is_some_and(|segment| segment.ident == "PhantomData") {
+            return;
+        }
+        for segment in segments {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                for arg in &args.args {
+                    if let syn::GenericArgument::Type(ty) = arg {
+                        self.walk(ty);
+                    }
+                }
+            }
+        }
+    }
+
+    fn mentions_param(&self, ty: &syn::Type) -> bool {
+        ty.to_token_stream().into_iter().any(|tt| match tt {
+            proc_macro2::TokenTree::Ident(ident) => self.params.contains(&ident),
+            _ => false,
+        })
+    }
+
+    fn push_bound(&mut self, ty: &syn::TypePath) {
+        let bound = &self.bound;
+        let predicate: syn::WherePredicate = syn::parse_quote!(#ty: #bound);
+        if self.seen.insert(quote!(#predicate).to_string()) {
+            self.predicates.push(predicate);
+        }
+    }
+
+    /// Adds handwritten predicates, skipping any that were already added.
+    pub fn extend(&mut self, predicates: impl IntoIterator<Item = syn::WherePredicate>) {
+        for predicate in predicates {
+            if self.seen.insert(quote!(#predicate).to_string()) {
+                self.predicates.push(predicate);
+            }
+        }
+    }
+
+    /// Returns the input generics with all collected predicates appended to
+    /// the where clause.
+    pub fn apply(self, generics: &syn::Generics) -> syn::Generics {
+        let mut generics = generics.clone();
+        generics.make_where_clause().predicates.extend(self.predicates);
+        generics
+    }
+}
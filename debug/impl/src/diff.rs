@@ -0,0 +1,138 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::DeriveInput;
+
+use crate::ast::{Field, Variant};
+use crate::attr::InferBounds;
+use crate::{attr, bound};
+
+/// `#[derive(CustomDebugDiff)]`: a `DebugDiff` impl and a `debug_diff`
+/// method. Fields are compared by what the Debug impl would print for them,
+/// so the `#[debug(...)]` field attributes apply here too; templates only
+/// affect how the value as a whole is printed and are ignored.
+pub(crate) fn expand(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let container_attrs = attr::parse_container_attrs(&ast.attrs)?;
+    let mut variants = match &ast.data {
+        syn::Data::Struct(data) => vec![Variant::from_struct(name, &data.fields, None)?],
+        syn::Data::Enum(data) => data.variants.iter().map(Variant::from_enum).collect::<syn::Result<_>>()?,
+        syn::Data::Union(data) => {
+            return Err(syn::Error::new_spanned(data.union_token, "CustomDebugDiff cannot be derived for unions"));
+        }
+    };
+    if let Some(except) = &container_attrs.redact_all_except {
+        crate::redact_all_except(except, &mut variants)?;
+    }
+
+    let infer = container_attrs.infer_bounds();
+    let mut bounds = bound::BoundInference::new(&ast.generics);
+    if infer == InferBounds::All {
+        bounds.all_params(syn::parse_quote!(std::fmt::Debug));
+    }
+    let mut arms = Vec::new();
+    for variant in &variants {
+        // A redacted field always prints the same, so it never differs.
+        let is_compared = |f: &Field| f.attrs.skip.is_none() && !f.attrs.is_redacted();
+        let compared: Vec<&Field> = variant.fields.iter().filter(|f| is_compared(f)).collect();
+        for f in &compared {
+            if let Some(bound) = &f.attrs.bound {
+                bounds.extend(bound.iter().cloned());
+            } else if infer != InferBounds::None && f.attrs.flatten.is_some() {
+                bounds.infer(f.ty, syn::parse_quote!(derive_debug::diff::DebugDiff));
+            } else if infer != InferBounds::None && f.attrs.uses_debug() {
+                bounds.infer(f.ty, syn::parse_quote!(std::fmt::Debug));
+            }
+        }
+
+        let left = variant.pattern(is_compared);
+        let right = variant.pattern_with(|f| if is_compared(f) { Some(other_binding(f)) } else { None });
+        // A transparent struct is its field, so it adds nothing to the path.
+        // A flattened struct's fields are printed as this one's, which is
+        // left to `Probe::flattened` since it depends on whether they can be
+        // compared one by one.
+        let diffs = compared.iter().map(|f| {
+            let field_str = &f.name;
+            let path = if container_attrs.transparent.is_some() {
+                quote!(path)
+            } else {
+                quote!(&derive_debug::diff::join(path, #field_str))
+            };
+            if f.attrs.flatten.is_some() {
+                let binding = &f.binding;
+                let other = other_binding(f);
+                quote! { (&&derive_debug::diff::Probe(#binding, #other)).flattened(path, #field_str, diffs); }
+            } else if f.attrs.uses_debug() && f.attrs.truncate.is_none() {
+                let binding = &f.binding;
+                let other = other_binding(f);
+                quote! { (&&derive_debug::diff::Probe(#binding, #other)).diff(#path, diffs); }
+            } else if f.attrs.truncate.is_some() {
+                // Values that differ only past the cut would print the same,
+                // so the full values decide and the truncated ones are shown.
+                let value = crate::field_value(f);
+                let other = crate::field_value_of(f, &other_binding(f));
+                let untruncated = |binding: &syn::Ident| match f.attrs.hex {
+                    Some(_) => quote! { &derive_debug::fmt::Hex::new(#binding, std::option::Option::None) },
+                    None => quote! { &#binding },
+                };
+                let (full, other_full) = (untruncated(&f.binding), untruncated(&other_binding(f)));
+                quote! { diffs.compare_truncated(#path, #full, #other_full, #value, #other); }
+            } else {
+                let value = crate::field_value(f);
+                let other = crate::field_value_of(f, &other_binding(f));
+                quote! { diffs.compare(#path, #value, #other); }
+            }
+        });
+        arms.push(quote! { (#left, #right) => { #(#diffs)* } });
+    }
+    if let Some(bound) = &container_attrs.bound {
+        bounds.extend(bound.iter().cloned());
+    }
+
+    // Values of different variants have no fields in common, so only the
+    // variant names are reported.
+    if variants.len() > 1 {
+        let names = variants.iter().map(|variant| {
+            let pattern = variant.pattern(|_| false);
+            let variant_str = &variant.name;
+            quote! { #pattern => #variant_str, }
+        });
+        arms.push(quote! {
+            _ => {
+                let name = |value: &Self| match value { #(#names)* };
+                diffs.variant(path, name(self), name(other));
+            }
+        });
+    }
+
+    let generics = bounds.apply(&ast.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let scrutinee = if variants.is_empty() { quote!(*self) } else { quote!((self, other)) };
+    Ok(quote! {
+        impl #impl_generics derive_debug::diff::DebugDiff for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn diff_fields(&self, other: &Self, path: &str, diffs: &mut derive_debug::diff::Diffs) {
+                #[allow(unused_imports)]
+                use derive_debug::diff::{ViaDebug as _, ViaDiff as _};
+                match #scrutinee {
+                    #(#arms)*
+                }
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Lists the fields that differ between `self` and `other`, one
+            /// per line, or returns `None` if there are none.
+            pub fn debug_diff(&self, other: &Self) -> std::option::Option<std::string::String> {
+                let mut diffs = derive_debug::diff::Diffs::new();
+                derive_debug::diff::DebugDiff::diff_fields(self, other, "", &mut diffs);
+                diffs.finish()
+            }
+        }
+    })
+}
+
+/// The local holding a field of `other`, next to the field of `self` bound
+/// to `__self_N`.
+fn other_binding(f: &Field) -> syn::Ident {
+    format_ident!("__other{}", f.binding.to_string().trim_start_matches("__self"))
+}
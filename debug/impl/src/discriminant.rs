@@ -0,0 +1,138 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::ast::{Style, Variant};
+use crate::attr::{EnumDiscriminant, Radix};
+
+/// How one variant is named under `#[debug(enum_discriminant)]`.
+pub(crate) enum Label {
+    /// The whole name, discriminant included, like `Status::Busy (0x02)`.
+    Const(String),
+    /// A unit variant whose discriminant is not a literal, so it is found
+    /// by casting the variant to the repr type when printing.
+    Cast(TokenStream),
+}
+
+/// Labels for every variant of `data`, in order. The discriminants come from
+/// the integer literals written on the variants where possible; enums with
+/// only unit variants may use any constant expression instead, since those
+/// can be cast to their repr type.
+pub(crate) fn labels(
+    ast: &syn::DeriveInput,
+    data: &syn::DataEnum,
+    variants: &[Variant],
+    attr: &EnumDiscriminant,
+) -> syn::Result<Vec<Label>> {
+    let Some(repr) = int_repr(&ast.attrs)? else {
+        return Err(syn::Error::new_spanned(
+            &attr.path,
+            "`enum_discriminant` requires an integer representation like `#[repr(u8)]`",
+        ));
+    };
+    let bits = bits(&repr);
+    let enum_name = &ast.ident;
+    let unit_only = variants.iter().all(|v| v.style == Style::Unit);
+
+    let mut labels = Vec::new();
+    let mut next = Some(0i128);
+    for (syn_variant, variant) in data.variants.iter().zip(variants) {
+        let value = match &syn_variant.discriminant {
+            Some((_, expr)) => literal_value(expr),
+            None => next,
+        };
+        // The bit pattern of a negative `isize` depends on the target.
+        let value = value.filter(|&value| value >= 0 || bits.is_some() || attr.radix == Radix::Decimal);
+        let prefix = format!("{}::{}", enum_name, variant.name);
+        let label = match value {
+            Some(value) => Label::Const(format!("{} ({})", prefix, format_value(value, bits, attr.radix))),
+            None if unit_only => {
+                let ident = &syn_variant.ident;
+                let format = format!("{} ({{:{}}})", prefix, runtime_spec(bits, attr.radix));
+                Label::Cast(quote! {
+                    f.write_fmt(std::format_args!(#format, Self::#ident as #repr))
+                })
+            }
+            None => {
+                let message = "`enum_discriminant` needs integer literal discriminants on an enum with fields";
+                return Err(match &syn_variant.discriminant {
+                    Some((_, expr)) => syn::Error::new_spanned(expr, message),
+                    None => syn::Error::new_spanned(&syn_variant.ident, message),
+                });
+            }
+        };
+        labels.push(label);
+        next = value.map(|value| value + 1);
+    }
+    Ok(labels)
+}
+
+/// The integer type named in `#[repr(...)]`, if any. `repr(C, u8)` counts,
+/// `repr(C)` alone does not.
+fn int_repr(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Ident>> {
+    const INTS: &[&str] = &[
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ];
+    let mut repr = None;
+    for attr in attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                if INTS.iter().any(|int| ident == int) {
+                    repr = Some(ident.clone());
+                }
+            }
+            // Skip the arguments of `align(N)` and `packed(N)`.
+            if meta.input.peek(syn::token::Paren) {
+                let _ = meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr)
+}
+
+/// The width of the repr type in bits, or `None` for `usize` and `isize`,
+/// whose width depends on the target.
+fn bits(repr: &syn::Ident) -> Option<u32> {
+    repr.to_string()[1..].parse().ok()
+}
+
+/// The value of a literal discriminant like `5`, `-5` or `0x05`.
+fn literal_value(expr: &syn::Expr) -> Option<i128> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }) => lit.base10_parse().ok(),
+        syn::Expr::Unary(syn::ExprUnary { op: syn::UnOp::Neg(_), expr, .. }) => literal_value(expr).map(|v| -v),
+        syn::Expr::Group(group) => literal_value(&group.expr),
+        syn::Expr::Paren(paren) => literal_value(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Formats a discriminant at compile time the same way `runtime_spec` does
+/// when printing. Hex and binary show the two's complement bit pattern of the
+/// repr type, padded to its full width.
+fn format_value(value: i128, bits: Option<u32>, radix: Radix) -> String {
+    let pattern = match bits {
+        Some(128) | None => value as u128,
+        Some(bits) => (value as u128) & ((1u128 << bits) - 1),
+    };
+    match (radix, bits) {
+        (Radix::Decimal, _) => value.to_string(),
+        (Radix::Hex, Some(bits)) => format!("{:#0width$x}", pattern, width = bits as usize / 4 + 2),
+        (Radix::Hex, None) => format!("{:#x}", pattern),
+        (Radix::Binary, Some(bits)) => format!("{:#0width$b}", pattern, width = bits as usize + 2),
+        (Radix::Binary, None) => format!("{:#b}", pattern),
+    }
+}
+
+fn runtime_spec(bits: Option<u32>, radix: Radix) -> String {
+    match (radix, bits) {
+        (Radix::Decimal, _) => String::new(),
+        (Radix::Hex, Some(bits)) => format!("#0{}x", bits / 4 + 2),
+        (Radix::Hex, None) => "#x".to_owned(),
+        (Radix::Binary, Some(bits)) => format!("#0{}b", bits + 2),
+        (Radix::Binary, None) => "#b".to_owned(),
+    }
+}
@@ -0,0 +1,120 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+use crate::ast::{Field, Variant};
+use crate::attr::{self, DisplayAttrs};
+use crate::{bound, template};
+
+/// `#[derive(CustomDisplay)]`: every struct, or every variant of an enum,
+/// needs either a `#[display("...")]` template or `#[display(transparent)]`.
+pub(crate) fn expand(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let mut container_attrs = attr::parse_display_attrs(&ast.attrs)?;
+    let container_bound = container_attrs.bound.take();
+    let variants: Vec<(Variant, DisplayAttrs)> = match &ast.data {
+        syn::Data::Struct(data) => vec![(Variant::from_struct(name, &data.fields, None)?, container_attrs)],
+        syn::Data::Enum(data) => {
+            if let Some(template) = &container_attrs.template {
+                return Err(syn::Error::new(
+                    template.span(),
+                    "templates on an enum go on each variant, like `#[display(\"...\")]`",
+                ));
+            }
+            if let Some(transparent) = &container_attrs.transparent {
+                return Err(syn::Error::new_spanned(transparent, "`transparent` on an enum goes on each variant"));
+            }
+            data.variants
+                .iter()
+                .map(|v| {
+                    let display_attrs = attr::parse_display_attrs(&v.attrs)?;
+                    if let Some(bound) = &display_attrs.bound {
+                        return Err(syn::Error::new_spanned(bound, "`bound` goes on the enum, not on a variant"));
+                    }
+                    Ok((Variant::from_enum(v)?, display_attrs))
+                })
+                .collect::<syn::Result<_>>()?
+        }
+        syn::Data::Union(data) => {
+            return Err(syn::Error::new_spanned(data.union_token, "CustomDisplay cannot be derived for unions"));
+        }
+    };
+
+    // Report a missing or misused attribute on every variant at once rather
+    // than one per compile.
+    let mut errors: Option<syn::Error> = None;
+    let mut bounds = bound::BoundInference::new(&ast.generics);
+    let mut arms = Vec::new();
+    for (variant, display_attrs) in &variants {
+        let (printed, body) = match variant_body(name, variant, display_attrs) {
+            Ok(result) => result,
+            Err(err) => {
+                match &mut errors {
+                    Some(errors) => errors.combine(err),
+                    None => errors = Some(err),
+                }
+                continue;
+            }
+        };
+        if container_bound.is_none() {
+            for (f, bound) in &printed {
+                bounds.infer(f.ty, bound.clone());
+            }
+        }
+        let pattern = variant.pattern(|f| printed.iter().any(|(printed, _)| printed.member == f.member));
+        arms.push(quote! { #pattern => { #body } });
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    if let Some(bound) = container_bound {
+        bounds.extend(bound);
+    }
+    let generics = bounds.apply(&ast.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let scrutinee = if variants.is_empty() { quote!(*self) } else { quote!(self) };
+    Ok(quote! {
+        impl #impl_generics std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match #scrutinee {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/// The fields a variant prints, each with the trait it is printed through,
+/// and the body of its match arm.
+fn variant_body<'v, 'a>(
+    name: &syn::Ident,
+    variant: &'v Variant<'a>,
+    display_attrs: &DisplayAttrs,
+) -> syn::Result<(Vec<(&'v Field<'a>, syn::Path)>, TokenStream)> {
+    if let Some(transparent) = &display_attrs.transparent {
+        let [f] = &variant.fields[..] else {
+            return Err(syn::Error::new_spanned(transparent, "`transparent` requires exactly one field"));
+        };
+        let binding = &f.binding;
+        let body = quote! { std::fmt::Display::fmt(#binding, f) };
+        Ok((vec![(f, syn::parse_quote!(std::fmt::Display))], body))
+    } else if let Some(template) = &display_attrs.template {
+        let printed = template
+            .fields(variant)?
+            .into_iter()
+            .map(|(f, spec)| (f, template::trait_for_spec(spec)))
+            .collect();
+        let body = template.write(variant, |f| {
+            let binding = &f.binding;
+            quote!(#binding)
+        })?;
+        Ok((printed, body))
+    } else {
+        let ident = variant.ident.unwrap_or(name);
+        Err(syn::Error::new_spanned(
+            ident,
+            format!("missing `#[display(\"...\")]` template or `#[display(transparent)]` for `{}`", ident),
+        ))
+    }
+}
@@ -0,0 +1,441 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+use quote::quote;
+use syn::ext::IdentExt;
+
+mod ast;
+mod attr;
+mod bound;
+mod diff;
+mod discriminant;
+mod display;
+mod structured;
+mod template;
+
+use crate::ast::{Field, Style, Variant};
+use crate::attr::InferBounds;
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand(&ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(CustomDebugDiff, attributes(debug))]
+pub fn derive_diff(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    diff::expand(&ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    display::expand(&ast).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let mut container_attrs = attr::parse_container_attrs(&ast.attrs)?;
+    if container_attrs.with.is_some() || container_attrs.opaque.is_some() {
+        return expand_whole_value(ast, container_attrs);
+    }
+    let mut variants = match &ast.data {
+        syn::Data::Struct(data) => {
+            vec![Variant::from_struct(name, &data.fields, container_attrs.template.take())?]
+        }
+        syn::Data::Enum(data) => {
+            if let Some(template) = &container_attrs.template {
+                return Err(syn::Error::new(
+                    template.span(),
+                    "templates on an enum go on each variant, like `#[debug(\"...\")]`",
+                ));
+            }
+            data.variants.iter().map(Variant::from_enum).collect::<syn::Result<_>>()?
+        }
+        syn::Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "CustomDebug on a union needs `#[debug(with = path)]` or `#[debug(opaque)]`",
+            ));
+        }
+    };
+    if let Some(transparent) = &container_attrs.transparent {
+        let is_newtype = matches!(ast.data, syn::Data::Struct(_)) && variants[0].fields.len() == 1;
+        if !is_newtype {
+            return Err(syn::Error::new_spanned(
+                transparent,
+                "`transparent` requires a struct with exactly one field",
+            ));
+        }
+        if variants[0].template.is_some() {
+            return Err(syn::Error::new_spanned(transparent, "`transparent` cannot be combined with a template"));
+        }
+        if let Some(skip) = &variants[0].fields[0].attrs.skip {
+            return Err(syn::Error::new_spanned(skip, "cannot skip the only field of a `transparent` struct"));
+        }
+    }
+    if let Some(except) = &container_attrs.redact_all_except {
+        redact_all_except(except, &mut variants)?;
+    }
+    if container_attrs.skip_none.is_some() {
+        skip_none(&mut variants);
+    }
+    let labels = match (&ast.data, &container_attrs.enum_discriminant) {
+        (syn::Data::Enum(data), Some(attr)) => Some(discriminant::labels(ast, data, &variants, attr)?),
+        (_, Some(attr)) => return Err(syn::Error::new_spanned(&attr.path, "`enum_discriminant` only applies to enums")),
+        (_, None) => None,
+    };
+
+    let infer = container_attrs.infer_bounds();
+    let mut bounds = bound::BoundInference::new(&ast.generics);
+    if infer == InferBounds::All {
+        bounds.all_params(syn::parse_quote!(std::fmt::Debug));
+    }
+    let mut arms = Vec::new();
+    let mut debug_fields = None;
+    for (i, variant) in variants.iter().enumerate() {
+        // A field-level bound replaces what this field would have
+        // contributed, whatever the strategy. A container-level bound is
+        // added to whatever the strategy infers, and without a strategy it
+        // turns inference off. Fields with their own format or function,
+        // and skipped or redacted ones, don't go through Debug at all.
+        // Fields printed by a template are bounded on whichever trait their
+        // placeholder uses, and fields the template doesn't mention need no
+        // bound.
+        let printed: Vec<(&Field, syn::Path)> = match &variant.template {
+            Some(template) => template
+                .fields(variant)?
+                .into_iter()
+                .map(|(f, spec)| (f, template::trait_for_spec(spec)))
+                .collect(),
+            None => variant
+                .fields
+                .iter()
+                .filter(|f| f.attrs.skip.is_none())
+                .map(|f| match f.attrs.flatten {
+                    Some(_) => (f, syn::parse_quote!(derive_debug::fmt::DebugFields)),
+                    None => (f, syn::parse_quote!(std::fmt::Debug)),
+                })
+                .collect(),
+        };
+        if variant.style != Style::Named {
+            if let Some(flatten) = variant.fields.iter().find_map(|f| f.attrs.flatten.as_ref()) {
+                return Err(syn::Error::new_spanned(flatten, "`flatten` only works on a named field"));
+            }
+        }
+        for (f, bound) in &printed {
+            if let Some(bound) = &f.attrs.bound {
+                bounds.extend(bound.iter().cloned());
+            } else if infer != InferBounds::None && (f.attrs.uses_debug() || f.attrs.flatten.is_some()) {
+                bounds.infer(f.ty, bound.clone());
+            }
+        }
+
+        let pattern = variant.pattern(|f| printed.iter().any(|(printed, _)| printed.member == f.member));
+        let label = labels.as_ref().map(|labels| &labels[i]);
+        let variant_str = match label {
+            Some(discriminant::Label::Const(label)) => label,
+            _ => &variant.name,
+        };
+        let finish = if variant.fields.iter().any(|f| f.attrs.skip.is_some()) {
+            quote!(finish_non_exhaustive)
+        } else {
+            quote!(finish)
+        };
+        if variant.ident.is_none() && variant.style == Style::Named && variant.template.is_none() {
+            debug_fields = Some((variant, named_fields(variant, &printed)));
+        }
+        let body = match variant.style {
+            _ if container_attrs.transparent.is_some() => {
+                let value = field_value(&variant.fields[0]);
+                quote! { std::fmt::Debug::fmt(#value, f) }
+            }
+            _ if variant.template.is_some() => variant.template.as_ref().unwrap().write(variant, field_value)?,
+            Style::Named if printed.iter().any(|(f, _)| f.attrs.flatten.is_some() || f.attrs.skip_if.is_some()) => {
+                let debug_fields = named_fields(variant, &printed);
+                quote! {
+                    let debug_struct = &mut f.debug_struct(#variant_str);
+                    #debug_fields
+                    if non_exhaustive {
+                        debug_struct.finish_non_exhaustive()
+                    } else {
+                        debug_struct.finish()
+                    }
+                }
+            }
+            Style::Named => {
+                let debug_fields = printed.iter().map(|(f, _)| {
+                    let field_str = &f.name;
+                    let value = field_value(f);
+                    quote! { .field(#field_str, #value) }
+                });
+                quote! {
+                    f.debug_struct(#variant_str)
+                        #(#debug_fields)*
+                        .#finish()
+                }
+            }
+            Style::Unnamed if printed.iter().any(|(f, _)| f.attrs.skip_if.is_some()) => {
+                let debug_fields = printed.iter().map(|(f, _)| {
+                    let value = field_value(f);
+                    skip_unless(f, quote! { debug_tuple.field(#value); })
+                });
+                quote! {
+                    let debug_tuple = &mut f.debug_tuple(#variant_str);
+                    #(#debug_fields)*
+                    debug_tuple.#finish()
+                }
+            }
+            Style::Unnamed => {
+                let debug_fields = printed.iter().map(|(f, _)| {
+                    let value = field_value(f);
+                    quote! { .field(#value) }
+                });
+                quote! {
+                    f.debug_tuple(#variant_str)
+                        #(#debug_fields)*
+                        .#finish()
+                }
+            }
+            Style::Unit => match label {
+                Some(discriminant::Label::Cast(write)) => write.clone(),
+                _ => quote! { f.write_str(#variant_str) },
+            },
+        };
+        arms.push(quote! { #pattern => { #body } });
+    }
+    if let Some(bound) = &container_attrs.bound {
+        bounds.extend(bound.iter().cloned());
+    }
+    let generics = bounds.apply(&ast.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // An enum without variants has no value to format, and matching on the
+    // reference would not be considered exhaustive.
+    let scrutinee = if variants.is_empty() { quote!(*self) } else { quote!(self) };
+    // Formatting again without the `#` flag makes every field below this
+    // one compact as well.
+    let compact = container_attrs.compact.as_ref().map(|_| {
+        quote! {
+            if f.alternate() {
+                return f.write_fmt(std::format_args!("{:?}", self));
+            }
+        }
+    });
+    let structured = structured::expand(
+        ast,
+        &generics,
+        &variants,
+        container_attrs.transparent.is_some(),
+        &container_attrs.formats,
+    );
+    // Structs that list named fields can have those fields flattened into
+    // another struct's output.
+    let debug_fields = debug_fields.filter(|_| container_attrs.transparent.is_none()).map(|(variant, fields)| {
+        let pattern = variant.pattern(|f| f.attrs.skip.is_none());
+        let visit_pattern = variant.pattern(structured::is_visited);
+        let visits = structured::visit_fields(variant);
+        quote! {
+            impl #impl_generics derive_debug::fmt::DebugFields for #name #ty_generics #where_clause {
+                fn debug_fields(&self, debug_struct: &mut std::fmt::DebugStruct<'_, '_>) -> bool {
+                    let #pattern = self;
+                    #fields
+                    non_exhaustive
+                }
+
+                fn visit_fields(&self, visitor: &mut dyn derive_debug::structured::Visitor) -> std::fmt::Result {
+                    #[allow(unused_imports)]
+                    use derive_debug::structured::{ViaDebug as _, ViaPrimitive as _, ViaStructured as _};
+                    let #visit_pattern = self;
+                    #(#visits)*
+                    std::result::Result::Ok(())
+                }
+            }
+        }
+    });
+    Ok(quote! {
+        impl #impl_generics std::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                #compact
+                match #scrutinee {
+                    #(#arms)*
+                }
+            }
+        }
+
+        #structured
+
+        #debug_fields
+    })
+}
+
+/// Statements adding the given fields to `debug_struct`, a `&mut DebugStruct`,
+/// and leaving in `non_exhaustive` whether any field was left out, either here
+/// or by a flattened struct.
+fn named_fields(variant: &Variant, printed: &[(&Field, syn::Path)]) -> proc_macro2::TokenStream {
+    let skipped = variant.fields.iter().any(|f| f.attrs.skip.is_some());
+    let flattened = printed.iter().any(|(f, _)| f.attrs.flatten.is_some());
+    let mutability = if flattened { Some(quote!(mut)) } else { None };
+    let fields = printed.iter().map(|(f, _)| {
+        let binding = &f.binding;
+        let stmt = if f.attrs.flatten.is_some() {
+            quote! { non_exhaustive |= derive_debug::fmt::DebugFields::debug_fields(#binding, debug_struct); }
+        } else {
+            let field_str = &f.name;
+            let value = field_value(f);
+            quote! { debug_struct.field(#field_str, #value); }
+        };
+        skip_unless(f, stmt)
+    });
+    quote! {
+        let #mutability non_exhaustive = #skipped;
+        #(#fields)*
+    }
+}
+
+/// Wraps the statement printing `f` in a check of its `skip_if` function.
+/// Such a field is left out of the output silently, without a trailing `..`.
+fn skip_unless(f: &Field, stmt: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    match &f.attrs.skip_if {
+        Some(skip_if) => {
+            let binding = &f.binding;
+            quote! {
+                if !#skip_if(#binding) {
+                    #stmt
+                }
+            }
+        }
+        None => stmt,
+    }
+}
+
+/// The Debug impl for `#[debug(with = path)]` and `#[debug(opaque)]`, which
+/// format the value without looking at its fields. This is the only way to
+/// derive CustomDebug for a union, since reading any field of one is unsafe.
+fn expand_whole_value(ast: &DeriveInput, container_attrs: attr::ContainerAttrs) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let body = match (&container_attrs.with, &container_attrs.opaque) {
+        (Some(with), _) => quote! { #with(self, f) },
+        (None, Some(opaque)) => {
+            let name_str = name.unraw().to_string();
+            if opaque.address {
+                quote! { f.write_fmt(std::format_args!("{} @ {:p}", #name_str, self)) }
+            } else {
+                quote! { f.write_str(#name_str) }
+            }
+        }
+        (None, None) => unreachable!(),
+    };
+    let mut bounds = bound::BoundInference::new(&ast.generics);
+    if container_attrs.infer == Some(InferBounds::All) {
+        bounds.all_params(syn::parse_quote!(std::fmt::Debug));
+    }
+    if let Some(bound) = container_attrs.bound {
+        bounds.extend(bound);
+    }
+    let generics = bounds.apply(&ast.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let structured = structured::expand_whole(ast, &generics, &container_attrs.formats);
+    Ok(quote! {
+        impl #impl_generics std::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                #body
+            }
+        }
+
+        #structured
+    })
+}
+
+/// The expression printed for one field, given its binding from the match
+/// arm. Evaluates to a reference to something implementing Debug; the
+/// adapters it uses live in the derive_debug crate's `fmt` module.
+fn field_value(f: &Field) -> proc_macro2::TokenStream {
+    field_value_of(f, &f.binding)
+}
+
+/// Like `field_value`, for a field bound to some other local.
+fn field_value_of(f: &Field, binding: &syn::Ident) -> proc_macro2::TokenStream {
+    if let Some(redact) = &f.attrs.redact {
+        if redact.len {
+            quote! { &std::format_args!("<redacted len={}>", #binding.len()) }
+        } else {
+            quote! { &"<redacted>" }
+        }
+    } else if f.attrs.hex.is_some() {
+        let limit = match &f.attrs.truncate {
+            Some(limit) => quote!(std::option::Option::Some(#limit)),
+            None => quote!(std::option::Option::None),
+        };
+        quote! { &derive_debug::fmt::Hex::new(#binding, #limit) }
+    } else if let Some(limit) = &f.attrs.truncate {
+        quote! { &derive_debug::fmt::Truncate::new(#binding, #limit) }
+    } else if let Some(format) = &f.attrs.format {
+        match template::alternate_format(&format.value()) {
+            Some(alternate) => {
+                let alternate = syn::LitStr::new(&alternate, format.span());
+                quote! {
+                    &derive_debug::fmt::Alternate::new(
+                        std::format_args!(#format, #binding),
+                        std::format_args!(#alternate, #binding),
+                    )
+                }
+            }
+            None => quote! { &std::format_args!(#format, #binding) },
+        }
+    } else if let Some(with) = &f.attrs.with {
+        quote! { &derive_debug::fmt::With::new(#binding, #with) }
+    } else {
+        quote! { &#binding }
+    }
+}
+
+/// Gives every `Option` field without a `skip` or `skip_if` of its own a
+/// `skip_if` that leaves it out when it is `None`.
+fn skip_none(variants: &mut [Variant]) {
+    for f in variants.iter_mut().flat_map(|v| &mut v.fields) {
+        if f.attrs.skip.is_none() && f.attrs.skip_if.is_none() && is_option(f.ty) {
+            f.attrs.skip_if = Some(syn::parse_quote!(std::option::Option::is_none));
+        }
+    }
+}
+
+/// Whether `ty` is written as `Option<T>`, the same check the builder derive
+/// uses. A path like `std::option::Option<T>` is not recognized.
+fn is_option(ty: &syn::Type) -> bool {
+    let syn::Type::Path(p) = ty else {
+        return false;
+    };
+    if p.qself.is_some() || p.path.segments.len() != 1 || p.path.segments[0].ident != "Option" {
+        return false;
+    }
+    match &p.path.segments[0].arguments {
+        syn::PathArguments::AngleBracketed(args) => {
+            args.args.len() == 1 && matches!(args.args[0], syn::GenericArgument::Type(_))
+        }
+        _ => false,
+    }
+}
+
+/// Marks every field not named in `#[debug(redact_all_except(...))]` as
+/// redacted, unless it already has its own `skip` or `redact`.
+fn redact_all_except(except: &[syn::Ident], variants: &mut [Variant]) -> syn::Result<()> {
+    for ident in except {
+        let known = variants.iter().flat_map(|v| &v.fields).any(|f| f.member == syn::Member::Named(ident.clone()));
+        if !known {
+            return Err(syn::Error::new_spanned(ident, format!("no field named `{}`", ident)));
+        }
+    }
+    for f in variants.iter_mut().flat_map(|v| &mut v.fields) {
+        let excepted = except.iter().any(|ident| f.member == syn::Member::Named(ident.clone()));
+        if !excepted && f.attrs.skip.is_none() && f.attrs.redact.is_none() {
+            // A flattened struct is redacted as a whole, under the field's
+            // own name, and hex or truncated output would still show part of
+            // the value.
+            f.attrs.flatten = None;
+            f.attrs.hex = None;
+            f.attrs.truncate = None;
+            f.attrs.redact = Some(attr::Redact { path: syn::parse_quote!(redact), len: false });
+        }
+    }
+    Ok(())
+}
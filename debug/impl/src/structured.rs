@@ -0,0 +1,145 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::ast::{Field, Variant};
+use crate::attr::StructuredFormat;
+
+/// The `Structured` impl behind `debug_logfmt` and `debug_json`, plus those
+/// methods for the formats the container asks for. The impl is generated for
+/// every type so that it can be nested inside one that asks for a format.
+pub(crate) fn expand(
+    ast: &syn::DeriveInput,
+    generics: &syn::Generics,
+    variants: &[Variant],
+    transparent: bool,
+    formats: &[StructuredFormat],
+) -> TokenStream {
+    let is_enum = matches!(ast.data, syn::Data::Enum(_));
+    let arms = variants.iter().map(|variant| {
+        // Templates and transparent structs format the value as a whole, so
+        // it is reported as one value rather than as fields.
+        if variant.template.is_some() {
+            let pattern = variant.pattern(|_| false);
+            return quote! {
+                #pattern => visitor.value(derive_debug::structured::Value::Debug(self)),
+            };
+        }
+        let pattern = variant.pattern(is_visited);
+        if transparent {
+            let value = value(&variant.fields[0]);
+            return quote! { #pattern => visitor.value(#value), };
+        }
+        let tag = if is_enum {
+            let variant_str = &variant.name;
+            Some(quote! { visitor.field("variant", derive_debug::structured::Value::Str(#variant_str))?; })
+        } else {
+            None
+        };
+        let fields = visit_fields(variant);
+        quote! {
+            #pattern => {
+                #tag
+                #(#fields)*
+                std::result::Result::Ok(())
+            }
+        }
+    });
+
+    let scrutinee = if variants.is_empty() { quote!(*self) } else { quote!(self) };
+    let body = quote! {
+        #[allow(unused_imports)]
+        use derive_debug::structured::{ViaDebug as _, ViaPrimitive as _, ViaStructured as _};
+        match #scrutinee {
+            #(#arms)*
+        }
+    };
+    wrap(ast, generics, body, formats)
+}
+
+/// The `Structured` impl for a type whose fields are not listed, like a union
+/// or an opaque type: it is reported as one value, the text its Debug impl
+/// prints.
+pub(crate) fn expand_whole(ast: &syn::DeriveInput, generics: &syn::Generics, formats: &[StructuredFormat]) -> TokenStream {
+    let body = quote! {
+        visitor.value(derive_debug::structured::Value::Debug(self))
+    };
+    wrap(ast, generics, body, formats)
+}
+
+fn wrap(ast: &syn::DeriveInput, generics: &syn::Generics, body: TokenStream, formats: &[StructuredFormat]) -> TokenStream {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let methods = formats.iter().map(|format| match format {
+        StructuredFormat::Logfmt => quote! {
+            /// Formats the fields as space-separated `key=value` pairs.
+            pub fn debug_logfmt(&self) -> impl std::fmt::Display + '_ {
+                derive_debug::structured::Logfmt(self)
+            }
+        },
+        StructuredFormat::Json => quote! {
+            /// Formats the fields as a JSON object.
+            pub fn debug_json(&self) -> impl std::fmt::Display + '_ {
+                derive_debug::structured::Json(self)
+            }
+        },
+    });
+    let inherent = if formats.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #(#methods)*
+            }
+        })
+    };
+
+    quote! {
+        impl #impl_generics derive_debug::structured::Structured for #name #ty_generics #where_clause {
+            fn visit(&self, visitor: &mut dyn derive_debug::structured::Visitor) -> std::fmt::Result {
+                #body
+            }
+        }
+
+        #inherent
+    }
+}
+
+/// Whether the visiting code reads the field. A redacted field's value is
+/// never reported, but `skip_if` may still need to look at it.
+pub(crate) fn is_visited(f: &Field) -> bool {
+    f.attrs.skip.is_none() && (!f.attrs.is_redacted() || f.attrs.skip_if.is_some())
+}
+
+/// Statements reporting each field that is not skipped to `visitor`. The
+/// fields of a flattened struct are reported as if they were this one's.
+pub(crate) fn visit_fields(variant: &Variant) -> Vec<TokenStream> {
+    variant
+        .fields
+        .iter()
+        .filter(|f| f.attrs.skip.is_none())
+        .map(|f| {
+            if f.attrs.flatten.is_some() {
+                let binding = &f.binding;
+                return crate::skip_unless(f, quote! { derive_debug::fmt::DebugFields::visit_fields(#binding, visitor)?; });
+            }
+            let field_str = &f.name;
+            let value = value(f);
+            crate::skip_unless(f, quote! { visitor.field(#field_str, #value)?; })
+        })
+        .collect()
+}
+
+/// The `Value` reported for one field. Fields printed through their own Debug
+/// impl are probed for a more specific representation; everything else, like
+/// a redacted or reformatted field, is reported as the text Debug would print.
+fn value(f: &Field) -> TokenStream {
+    if f.attrs.is_redacted() {
+        quote! { derive_debug::structured::Value::Str("<redacted>") }
+    } else if f.attrs.uses_debug() && f.attrs.truncate.is_none() {
+        let binding = &f.binding;
+        quote! { (&&&derive_debug::structured::Probe(&#binding)).structured_value() }
+    } else {
+        let value = crate::field_value(f);
+        quote! { derive_debug::structured::Value::Debug(#value) }
+    }
+}
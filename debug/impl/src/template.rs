@@ -0,0 +1,213 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+use crate::ast::{Field, Variant};
+
+/// A whole-value format string such as `"Point({x}, {y:.2})"`, whose
+/// placeholders name fields instead of arguments.
+pub(crate) struct Template {
+    lit: syn::LitStr,
+    pieces: Vec<Piece>,
+}
+
+enum Piece {
+    Text(String),
+    Field { member: syn::Member, spec: String },
+}
+
+impl Template {
+    pub fn parse(lit: &syn::LitStr) -> syn::Result<Self> {
+        let value = lit.value();
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = value.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let Some(end) = value[start..].find('}').map(|i| start + i) else {
+                        return Err(syn::Error::new(lit.span(), "unterminated `{` in template"));
+                    };
+                    while chars.next_if(|&(i, _)| i <= end).is_some() {}
+                    let inner = &value[start + 1..end];
+                    let (name, spec) = inner.split_once(':').unwrap_or((inner, ""));
+                    let member = parse_member(name.trim()).ok_or_else(|| {
+                        let message = format!("template placeholder `{{{}}}` must name a field, like `{{x}}` or `{{0}}`", inner);
+                        syn::Error::new(lit.span(), message)
+                    })?;
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    pieces.push(Piece::Field { member, spec: spec.to_owned() });
+                }
+                '}' => {
+                    return Err(syn::Error::new(lit.span(), "unmatched `}` in template; use `}}` for a literal brace"));
+                }
+                c => text.push(c),
+            }
+        }
+        pieces.push(Piece::Text(text));
+        Ok(Template { lit: lit.clone(), pieces })
+    }
+
+    pub fn span(&self) -> Span {
+        self.lit.span()
+    }
+
+    /// The fields this template refers to, in order of first use, checked
+    /// against the fields that actually exist.
+    pub fn fields<'v, 'a>(&self, variant: &'v Variant<'a>) -> syn::Result<Vec<(&'v Field<'a>, &str)>> {
+        let mut used = Vec::new();
+        for piece in &self.pieces {
+            let Piece::Field { member, spec } = piece else {
+                continue;
+            };
+            let Some(f) = variant.fields.iter().find(|f| f.member == *member) else {
+                let name = match member {
+                    syn::Member::Named(ident) => ident.to_string(),
+                    syn::Member::Unnamed(index) => index.index.to_string(),
+                };
+                return Err(syn::Error::new(self.lit.span(), format!("no field `{}` on `{}`", name, variant.name)));
+            };
+            used.push((f, spec.as_str()));
+        }
+        Ok(used)
+    }
+
+    /// Expands to a `write!` of the template into `f`, where `value` gives the
+    /// expression passed for each referenced field.
+    pub fn write(&self, variant: &Variant, mut value: impl FnMut(&Field) -> TokenStream) -> syn::Result<TokenStream> {
+        self.fields(variant)?;
+        let mut format = String::new();
+        let mut args: Vec<&syn::Member> = Vec::new();
+        let mut values = Vec::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => format.push_str(&text.replace('{', "{{").replace('}', "}}")),
+                Piece::Field { member, spec, .. } => {
+                    let index = match args.iter().position(|arg| *arg == member) {
+                        Some(index) => index,
+                        None => {
+                            let f = variant.fields.iter().find(|f| f.member == *member).unwrap();
+                            args.push(member);
+                            values.push(value(f));
+                            args.len() - 1
+                        }
+                    };
+                    if spec.is_empty() {
+                        format.push_str(&format!("{{{}}}", index));
+                    } else {
+                        format.push_str(&format!("{{{}:{}}}", index, spec));
+                    }
+                }
+            }
+        }
+        // Fields printed with `{:?}` are pretty-printed too when the value as
+        // a whole is formatted with `{:#?}`.
+        let alternate = alternate_format(&format);
+        let format = syn::LitStr::new(&format, self.lit.span());
+        Ok(match alternate {
+            Some(alternate) => {
+                let alternate = syn::LitStr::new(&alternate, self.lit.span());
+                quote! {
+                    if f.alternate() {
+                        f.write_fmt(std::format_args!(#alternate #(, #values)*))
+                    } else {
+                        f.write_fmt(std::format_args!(#format #(, #values)*))
+                    }
+                }
+            }
+            None => quote! {
+                f.write_fmt(std::format_args!(#format #(, #values)*))
+            },
+        })
+    }
+}
+
+fn parse_member(name: &str) -> Option<syn::Member> {
+    if !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()) {
+        return name.parse::<usize>().ok().map(|index| syn::Member::Unnamed(index.into()));
+    }
+    syn::parse_str::<syn::Ident>(name).ok().map(syn::Member::Named)
+}
+
+/// The same format string with `#` added to every `?` spec that lacks it, so
+/// that `{:?}` becomes `{:#?}`, or `None` if there is nothing to change. The
+/// string is assumed to be valid; other specs are left alone, since `#`
+/// changes what they print rather than just the layout.
+pub(crate) fn alternate_format(format: &str) -> Option<String> {
+    let mut alternate = String::with_capacity(format.len() + 1);
+    let mut changed = false;
+    let mut rest = format;
+    while let Some(start) = rest.find(['{', '}']) {
+        alternate.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(escaped) = ["{{", "}}", "}"].into_iter().find(|escaped| rest.starts_with(escaped)) {
+            alternate.push_str(escaped);
+            rest = &rest[escaped.len()..];
+            continue;
+        }
+        let end = rest.find('}')?;
+        let placeholder = &rest[..end + 1];
+        rest = &rest[end + 1..];
+        match placeholder[1..end].split_once(':') {
+            Some((arg, spec)) if spec.ends_with('?') => match alternate_spec(spec) {
+                Some(spec) => {
+                    alternate.push_str(&format!("{{{}:{}}}", arg, spec));
+                    changed = true;
+                }
+                None => alternate.push_str(placeholder),
+            },
+            _ => alternate.push_str(placeholder),
+        }
+    }
+    alternate.push_str(rest);
+    if changed {
+        Some(alternate)
+    } else {
+        None
+    }
+}
+
+/// Inserts `#` into a spec such as `>8?`, after the fill, alignment and sign
+/// that precede it in the format spec grammar.
+fn alternate_spec(spec: &str) -> Option<String> {
+    let chars: Vec<char> = spec.chars().collect();
+    let is_align = |c: Option<&char>| matches!(c, Some('<' | '^' | '>'));
+    let mut i = if is_align(chars.get(1)) {
+        2
+    } else if is_align(chars.first()) {
+        1
+    } else {
+        0
+    };
+    if matches!(chars.get(i), Some('+' | '-')) {
+        i += 1;
+    }
+    if chars.get(i) == Some(&'#') {
+        return None;
+    }
+    let mut spec: String = chars[..i].iter().collect();
+    spec.push('#');
+    spec.extend(&chars[i..]);
+    Some(spec)
+}
+
+/// The trait that a format spec such as `x`, `08b` or `?` dispatches to.
+pub(crate) fn trait_for_spec(spec: &str) -> syn::Path {
+    match spec.chars().last() {
+        Some('?') => syn::parse_quote!(std::fmt::Debug),
+        Some('x') => syn::parse_quote!(std::fmt::LowerHex),
+        Some('X') => syn::parse_quote!(std::fmt::UpperHex),
+        Some('b') => syn::parse_quote!(std::fmt::Binary),
+        Some('o') => syn::parse_quote!(std::fmt::Octal),
+        Some('e') => syn::parse_quote!(std::fmt::LowerExp),
+        Some('E') => syn::parse_quote!(std::fmt::UpperExp),
+        _ => syn::parse_quote!(std::fmt::Display),
+    }
+}
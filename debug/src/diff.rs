@@ -0,0 +1,118 @@
+//! Support code for `#[derive(CustomDebugDiff)]`, which compares two values
+//! field by field and reports only the fields that differ.
+
+use std::fmt::Debug;
+
+/// Implemented by `#[derive(CustomDebugDiff)]`.
+pub trait DebugDiff {
+    /// Records every difference between `self` and `other` into `diffs`,
+    /// with field paths starting at `path`.
+    fn diff_fields(&self, other: &Self, path: &str, diffs: &mut Diffs);
+}
+
+/// The differences found so far, one line each.
+#[derive(Default)]
+pub struct Diffs {
+    lines: Vec<String>,
+}
+
+impl Diffs {
+    pub fn new() -> Self {
+        Diffs::default()
+    }
+
+    /// Records `path` if the two values print differently.
+    pub fn compare(&mut self, path: &str, left: &dyn Debug, right: &dyn Debug) {
+        let left = format!("{:?}", left);
+        let right = format!("{:?}", right);
+        if left != right {
+            self.lines.push(format!("{}: {} != {}", label(path), left, right));
+        }
+    }
+
+    /// Like `compare` for a field printed truncated: the full values decide
+    /// whether they differ, so a difference past the cut still counts, but
+    /// the line shows them as printed.
+    pub fn compare_truncated(
+        &mut self,
+        path: &str,
+        left: &dyn Debug,
+        right: &dyn Debug,
+        left_shown: &dyn Debug,
+        right_shown: &dyn Debug,
+    ) {
+        if format!("{:?}", left) != format!("{:?}", right) {
+            self.lines.push(format!("{}: {:?} != {:?}", label(path), left_shown, right_shown));
+        }
+    }
+
+    /// Records that the two values are different variants of an enum.
+    pub fn variant(&mut self, path: &str, left: &str, right: &str) {
+        self.lines.push(format!("{}: variant {} != {}", label(path), left, right));
+    }
+
+    /// All differences, one per line, or `None` if there were none.
+    pub fn finish(self) -> Option<String> {
+        if self.lines.is_empty() {
+            None
+        } else {
+            Some(self.lines.join("\n"))
+        }
+    }
+}
+
+/// The path of the field `name` inside the value at `path`.
+pub fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn label(path: &str) -> &str {
+    if path.is_empty() {
+        "self"
+    } else {
+        path
+    }
+}
+
+/// Compares one pair of fields through autoref-based specialization:
+/// `(&&Probe(left, right)).diff(path, diffs)` recurses into types that
+/// implement [`DebugDiff`] and compares the `Debug` output of anything else.
+/// `flattened` does the same for a `#[debug(flatten)]` field `name`, whose
+/// fields belong to `path` itself when it recurses but which is compared at
+/// its own path when it doesn't. The traits have to be in scope at the call
+/// site.
+pub struct Probe<'a, T: ?Sized>(pub &'a T, pub &'a T);
+
+pub trait ViaDiff {
+    fn diff(&self, path: &str, diffs: &mut Diffs);
+    fn flattened(&self, path: &str, name: &str, diffs: &mut Diffs);
+}
+
+pub trait ViaDebug {
+    fn diff(&self, path: &str, diffs: &mut Diffs);
+    fn flattened(&self, path: &str, name: &str, diffs: &mut Diffs);
+}
+
+impl<'a, T: ?Sized + DebugDiff> ViaDiff for &Probe<'a, T> {
+    fn diff(&self, path: &str, diffs: &mut Diffs) {
+        self.0.diff_fields(self.1, path, diffs);
+    }
+
+    fn flattened(&self, path: &str, _name: &str, diffs: &mut Diffs) {
+        self.diff(path, diffs);
+    }
+}
+
+impl<'a, T: ?Sized + Debug> ViaDebug for Probe<'a, T> {
+    fn diff(&self, path: &str, diffs: &mut Diffs) {
+        diffs.compare(path, &self.0, &self.1);
+    }
+
+    fn flattened(&self, path: &str, name: &str, diffs: &mut Diffs) {
+        self.diff(&join(path, name), diffs);
+    }
+}
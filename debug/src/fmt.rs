@@ -0,0 +1,203 @@
+//! Adapters used by the code that `#[derive(CustomDebug)]` generates. They are
+//! ordinary `Debug` wrappers and can be used directly in handwritten impls too.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, DebugStruct, Formatter};
+
+use crate::structured::Visitor;
+
+/// Formats a value through a function instead of its own `Debug` impl, as
+/// requested by `#[debug(with = path)]`.
+pub struct With<'a, T: ?Sized> {
+    value: &'a T,
+    fmt: fn(&T, &mut Formatter<'_>) -> fmt::Result,
+}
+
+impl<'a, T: ?Sized> With<'a, T> {
+    pub fn new(value: &'a T, fmt: fn(&T, &mut Formatter<'_>) -> fmt::Result) -> Self {
+        With { value, fmt }
+    }
+}
+
+impl<'a, T: ?Sized> Debug for With<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (self.fmt)(self.value, f)
+    }
+}
+
+/// A field formatted by `#[debug = "..."]`, with a second rendering whose
+/// `{:?}` placeholders are pretty-printed, used when the value as a whole is
+/// formatted with `{:#?}`.
+pub struct Alternate<'a> {
+    plain: fmt::Arguments<'a>,
+    alternate: fmt::Arguments<'a>,
+}
+
+impl<'a> Alternate<'a> {
+    pub fn new(plain: fmt::Arguments<'a>, alternate: fmt::Arguments<'a>) -> Self {
+        Alternate { plain, alternate }
+    }
+}
+
+impl<'a> Debug for Alternate<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_fmt(self.alternate)
+        } else {
+            f.write_fmt(self.plain)
+        }
+    }
+}
+
+/// Prints at most `limit` elements of a sequence, or characters of a string,
+/// followed by `… (+N more)`, as requested by `#[debug(truncate = N)]`.
+pub struct Truncate<'a, T: ?Sized> {
+    value: &'a T,
+    limit: usize,
+}
+
+impl<'a, T: ?Sized + TruncateDebug> Truncate<'a, T> {
+    pub fn new(value: &'a T, limit: usize) -> Self {
+        Truncate { value, limit }
+    }
+}
+
+impl<'a, T: ?Sized + TruncateDebug> Debug for Truncate<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.value.fmt_truncated(self.limit, f)
+    }
+}
+
+/// Types that `#[debug(truncate = N)]` knows how to shorten.
+pub trait TruncateDebug {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result;
+}
+
+impl TruncateDebug for str {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.char_indices().nth(limit) {
+            None => Debug::fmt(self, f),
+            Some((end, _)) => {
+                Debug::fmt(&self[..end], f)?;
+                write!(f, "{}", More(self[end..].chars().count()))
+            }
+        }
+    }
+}
+
+impl TruncateDebug for String {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt_truncated(limit, f)
+    }
+}
+
+impl<T: Debug> TruncateDebug for [T] {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_list(self.iter(), self.len(), limit, f)
+    }
+}
+
+impl<T: Debug, const N: usize> TruncateDebug for [T; N] {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt_truncated(limit, f)
+    }
+}
+
+impl<T: Debug> TruncateDebug for Vec<T> {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt_truncated(limit, f)
+    }
+}
+
+impl<T: Debug> TruncateDebug for VecDeque<T> {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_list(self.iter(), self.len(), limit, f)
+    }
+}
+
+impl<T: ?Sized + TruncateDebug> TruncateDebug for &T {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result {
+        (**self).fmt_truncated(limit, f)
+    }
+}
+
+impl<T: ?Sized + TruncateDebug> TruncateDebug for Box<T> {
+    fn fmt_truncated(&self, limit: usize, f: &mut Formatter<'_>) -> fmt::Result {
+        (**self).fmt_truncated(limit, f)
+    }
+}
+
+fn fmt_list<T: Debug>(
+    iter: impl Iterator<Item = T>,
+    len: usize,
+    limit: usize,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let mut list = f.debug_list();
+    list.entries(iter.take(limit));
+    if len > limit {
+        list.entry(&More(len - limit));
+    }
+    list.finish()
+}
+
+/// Prints bytes as a compact hex dump like `[de ad be ef]`, as requested by
+/// `#[debug(hex)]`. With a limit, only that many bytes are shown.
+pub struct Hex<'a> {
+    bytes: &'a [u8],
+    limit: Option<usize>,
+}
+
+impl<'a> Hex<'a> {
+    pub fn new<T: ?Sized + AsRef<[u8]>>(value: &'a T, limit: Option<usize>) -> Self {
+        Hex { bytes: value.as_ref(), limit }
+    }
+}
+
+impl<'a> Debug for Hex<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let shown = self.limit.map_or(self.bytes.len(), |limit| limit.min(self.bytes.len()));
+        f.write_str("[")?;
+        for (i, byte) in self.bytes[..shown].iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        if shown < self.bytes.len() {
+            if shown > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", More(self.bytes.len() - shown))?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// The marker printed in place of elements that were cut off.
+struct More(usize);
+
+impl Debug for More {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for More {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "… (+{} more)", self.0)
+    }
+}
+
+/// Implemented by `#[derive(CustomDebug)]` for structs with named fields, so
+/// that another struct can print their fields as its own through
+/// `#[debug(flatten)]`.
+#[doc(hidden)]
+pub trait DebugFields {
+    /// Adds the fields to `debug_struct` and returns whether any were left
+    /// out, in which case the output should end with `..`.
+    fn debug_fields(&self, debug_struct: &mut DebugStruct<'_, '_>) -> bool;
+
+    /// Reports the fields to a structured output visitor.
+    fn visit_fields(&self, visitor: &mut dyn Visitor) -> fmt::Result;
+}
@@ -1,8 +1,11 @@
-use proc_macro::TokenStream;
+// Crates that have the "proc-macro" crate type are only allowed to export
+// procedural macros, but some of the formatting options need code that runs
+// when the value is printed. As with the bitfield project, the derives live in
+// a separate derive_debug_impl crate and are re-exported from here together
+// with the runtime helpers in `fmt`, `structured` and `diff`, so users only
+// import one crate.
+pub use derive_debug_impl::{CustomDebug, CustomDebugDiff, CustomDisplay};
 
-#[proc_macro_derive(CustomDebug)]
-pub fn derive(input: TokenStream) -> TokenStream {
-    let _ = input;
-
-    unimplemented!()
-}
+pub mod diff;
+pub mod fmt;
+pub mod structured;
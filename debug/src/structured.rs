@@ -0,0 +1,272 @@
+//! Key/value output for `#[debug(format = "logfmt")]` and
+//! `#[debug(format = "json")]`.
+//!
+//! Every type deriving `CustomDebug` implements [`Structured`], which walks the
+//! same fields its `Debug` impl prints. [`Logfmt`] and [`Json`] turn that walk
+//! into text, descending into fields whose types derive `CustomDebug` too.
+
+use std::fmt::{self, Debug, Display, Formatter, Write};
+
+/// Implemented by `#[derive(CustomDebug)]`: reports the printed fields of a
+/// value to a [`Visitor`].
+pub trait Structured {
+    fn visit(&self, visitor: &mut dyn Visitor) -> fmt::Result;
+}
+
+/// Receives the fields of a [`Structured`] value, or the value as a whole for
+/// types that are formatted from a template or are transparent.
+pub trait Visitor {
+    fn field(&mut self, key: &str, value: Value<'_>) -> fmt::Result;
+    fn value(&mut self, value: Value<'_>) -> fmt::Result;
+}
+
+/// One value reported to a [`Visitor`].
+pub enum Value<'a> {
+    Str(&'a str),
+    Bool(bool),
+    /// An integer, written as is.
+    Int(&'a dyn Display),
+    /// A float, written as is through its own type's `Display` so that an
+    /// `f32` is not widened to an `f64` with more digits.
+    Float { value: &'a dyn Display, finite: bool },
+    /// Anything else, written as the string its `Debug` impl produces.
+    Debug(&'a dyn Debug),
+    Nested(&'a dyn Structured),
+}
+
+/// Values with a natural representation in logfmt and JSON.
+pub trait Primitive {
+    fn primitive(&self) -> Value<'_>;
+}
+
+impl Primitive for str {
+    fn primitive(&self) -> Value<'_> {
+        Value::Str(self)
+    }
+}
+
+impl Primitive for String {
+    fn primitive(&self) -> Value<'_> {
+        Value::Str(self)
+    }
+}
+
+impl Primitive for bool {
+    fn primitive(&self) -> Value<'_> {
+        Value::Bool(*self)
+    }
+}
+
+impl<T: ?Sized + Primitive> Primitive for &T {
+    fn primitive(&self) -> Value<'_> {
+        (**self).primitive()
+    }
+}
+
+macro_rules! primitive {
+    (int: $($int:ty)*; float: $($float:ty)*) => {
+        $(
+            impl Primitive for $int {
+                fn primitive(&self) -> Value<'_> {
+                    Value::Int(self)
+                }
+            }
+        )*
+        $(
+            impl Primitive for $float {
+                fn primitive(&self) -> Value<'_> {
+                    Value::Float { value: self, finite: self.is_finite() }
+                }
+            }
+        )*
+    };
+}
+
+primitive!(int: u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize; float: f32 f64);
+
+/// Picks the most specific [`Value`] for a field through autoref-based
+/// specialization: `(&&&Probe(&field)).structured_value()` prefers a nested
+/// [`Structured`] value, then a [`Primitive`], and falls back to `Debug`. The
+/// traits have to be in scope at the call site. The field is passed by double
+/// reference, so that even an unsized one can fall back to `Debug`.
+pub struct Probe<'a, T: ?Sized>(pub &'a &'a T);
+
+pub trait ViaStructured<'a> {
+    fn structured_value(&self) -> Value<'a>;
+}
+
+pub trait ViaPrimitive<'a> {
+    fn structured_value(&self) -> Value<'a>;
+}
+
+pub trait ViaDebug<'a> {
+    fn structured_value(&self) -> Value<'a>;
+}
+
+impl<'a, T: Structured> ViaStructured<'a> for &&Probe<'a, T> {
+    fn structured_value(&self) -> Value<'a> {
+        Value::Nested(*self.0)
+    }
+}
+
+impl<'a, T: ?Sized + Primitive> ViaPrimitive<'a> for &Probe<'a, T> {
+    fn structured_value(&self) -> Value<'a> {
+        T::primitive(*self.0)
+    }
+}
+
+impl<'a, T: ?Sized + Debug> ViaDebug<'a> for Probe<'a, T> {
+    fn structured_value(&self) -> Value<'a> {
+        Value::Debug(self.0)
+    }
+}
+
+/// Formats a value as space-separated `key=value` pairs. Nested values use
+/// dotted keys, like `config.retries=3`.
+pub struct Logfmt<'a, T: ?Sized>(pub &'a T);
+
+impl<'a, T: ?Sized + Structured> Display for Logfmt<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut writer = LogfmtWriter { f, prefix: String::new(), first: true };
+        self.0.visit(&mut writer)
+    }
+}
+
+struct LogfmtWriter<'a, 'f> {
+    f: &'a mut Formatter<'f>,
+    prefix: String,
+    first: bool,
+}
+
+impl<'a, 'f> LogfmtWriter<'a, 'f> {
+    fn pair(&mut self, key: String, value: Value<'_>) -> fmt::Result {
+        if let Value::Nested(nested) = value {
+            let mut writer = LogfmtWriter { f: &mut *self.f, prefix: key, first: self.first };
+            nested.visit(&mut writer)?;
+            self.first = writer.first;
+            return Ok(());
+        }
+        if !self.first {
+            self.f.write_char(' ')?;
+        }
+        self.first = false;
+        write!(self.f, "{}=", key)?;
+        match value {
+            Value::Str(s) => write_logfmt_str(self.f, s),
+            Value::Bool(b) => write!(self.f, "{}", b),
+            Value::Int(n) => write!(self.f, "{}", n),
+            Value::Float { value, .. } => write!(self.f, "{}", value),
+            Value::Debug(value) => write_logfmt_str(self.f, &format!("{:?}", value)),
+            Value::Nested(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a, 'f> Visitor for LogfmtWriter<'a, 'f> {
+    fn field(&mut self, key: &str, value: Value<'_>) -> fmt::Result {
+        let key = if self.prefix.is_empty() { key.to_owned() } else { format!("{}.{}", self.prefix, key) };
+        self.pair(key, value)
+    }
+
+    fn value(&mut self, value: Value<'_>) -> fmt::Result {
+        let key = if self.prefix.is_empty() { "value".to_owned() } else { self.prefix.clone() };
+        self.pair(key, value)
+    }
+}
+
+/// Quotes a logfmt value if it is empty or contains spaces, `=`, quotes or
+/// control characters.
+fn write_logfmt_str(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    let needs_quotes = s.is_empty() || s.chars().any(|c| c == ' ' || c == '=' || c == '"' || c.is_control());
+    if !needs_quotes {
+        return f.write_str(s);
+    }
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Formats a value as a JSON object. Nested values become nested objects.
+pub struct Json<'a, T: ?Sized>(pub &'a T);
+
+impl<'a, T: ?Sized + Structured> Display for Json<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_json_nested(f, self.0)
+    }
+}
+
+#[derive(PartialEq)]
+enum JsonState {
+    Empty,
+    Object,
+    Value,
+}
+
+struct JsonWriter<'a, 'f> {
+    f: &'a mut Formatter<'f>,
+    state: JsonState,
+}
+
+impl<'a, 'f> Visitor for JsonWriter<'a, 'f> {
+    fn field(&mut self, key: &str, value: Value<'_>) -> fmt::Result {
+        self.f.write_str(if self.state == JsonState::Empty { "{" } else { "," })?;
+        self.state = JsonState::Object;
+        write_json_str(self.f, key)?;
+        self.f.write_char(':')?;
+        write_json_value(self.f, value)
+    }
+
+    fn value(&mut self, value: Value<'_>) -> fmt::Result {
+        self.state = JsonState::Value;
+        write_json_value(self.f, value)
+    }
+}
+
+fn write_json_nested(f: &mut Formatter<'_>, nested: &(impl Structured + ?Sized)) -> fmt::Result {
+    let mut writer = JsonWriter { f, state: JsonState::Empty };
+    nested.visit(&mut writer)?;
+    match writer.state {
+        JsonState::Empty => writer.f.write_str("{}"),
+        JsonState::Object => writer.f.write_char('}'),
+        JsonState::Value => Ok(()),
+    }
+}
+
+fn write_json_value(f: &mut Formatter<'_>, value: Value<'_>) -> fmt::Result {
+    match value {
+        Value::Str(s) => write_json_str(f, s),
+        Value::Bool(b) => write!(f, "{}", b),
+        Value::Int(n) => write!(f, "{}", n),
+        // JSON has no representation for NaN or the infinities.
+        Value::Float { finite: false, .. } => f.write_str("null"),
+        Value::Float { value, .. } => write!(f, "{}", value),
+        Value::Debug(value) => write_json_str(f, &format!("{:?}", value)),
+        Value::Nested(nested) => write_json_nested(f, nested),
+    }
+}
+
+fn write_json_str(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
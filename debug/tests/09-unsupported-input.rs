@@ -0,0 +1,15 @@
+// CustomDebug cannot know which field of a union holds a valid value, and
+// reading the wrong one is undefined behavior, so it never generates an
+// accessor on its own. A union without #[debug(with = ...)] or
+// #[debug(opaque)] should be rejected with an error pointing at the item
+// keyword instead of a panic inside the macro.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
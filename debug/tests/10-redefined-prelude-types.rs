@@ -0,0 +1,29 @@
+// The generated impl must keep working when names it relies on mean something
+// else in the caller's scope, just like the builder's
+// 09-redefined-prelude-types.rs. Refer to everything through absolute paths.
+
+use derive_debug::CustomDebug;
+
+type Option = ();
+type Some = ();
+type None = ();
+type Result = ();
+type Box = ();
+struct Formatter;
+mod fmt {}
+
+#[derive(CustomDebug)]
+pub struct Field {
+    name: &'static str,
+    bitmask: u8,
+}
+
+fn main() {
+    let f = Field {
+        name: "F",
+        bitmask: 0b00011100,
+    };
+
+    let debug = format!("{:?}", f);
+    assert_eq!(debug, r#"Field { name: "F", bitmask: 28 }"#);
+}
@@ -0,0 +1,44 @@
+// Some field types have no Debug impl we can use, or one we don't control,
+// like raw pointers or types from other crates. #[debug(with = path)] names a
+// function `fn(&T, &mut Formatter) -> fmt::Result` that formats the field
+// instead. The path may be written bare or inside a string literal.
+
+use derive_debug::CustomDebug;
+use std::fmt;
+use std::time::Instant;
+
+mod fmt_helpers {
+    pub fn elapsed(_: &std::time::Instant, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("<instant>")
+    }
+}
+
+fn address<T>(ptr: &*const T, f: &mut fmt::Formatter) -> fmt::Result {
+    if ptr.is_null() {
+        f.write_str("null")
+    } else {
+        f.write_str("non-null")
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Event {
+    name: &'static str,
+    #[debug(with = "fmt_helpers::elapsed")]
+    started: Instant,
+    #[debug(with = address)]
+    payload: *const u8,
+}
+
+fn main() {
+    let event = Event {
+        name: "tick",
+        started: Instant::now(),
+        payload: std::ptr::null(),
+    };
+
+    let debug = format!("{:?}", event);
+    let expected = r#"Event { name: "tick", started: <instant>, payload: null }"#;
+
+    assert_eq!(debug, expected);
+}
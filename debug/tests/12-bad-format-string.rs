@@ -0,0 +1,39 @@
+// The field format string is handed to format_args! along with the field
+// value, so it has to contain exactly one placeholder. Check this while
+// expanding the macro so that a typo is reported at the attribute rather than
+// somewhere inside the generated impl.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Missing {
+    #[debug = "0b"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct TooMany {
+    #[debug = "{:x}/{:o}"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Named {
+    #[debug = "{bitmask:08b}"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Unbalanced {
+    #[debug = "{:08b"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Conflicting {
+    #[debug = "{:08b}"]
+    #[debug(with = "std::fmt::Binary::fmt")]
+    bitmask: u8,
+}
+
+fn main() {}
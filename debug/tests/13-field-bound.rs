@@ -0,0 +1,47 @@
+// A #[debug(bound = "...")] attribute on a single field substitutes only the
+// bounds that would have been inferred from that field's type. Bounds inferred
+// from the other fields are kept.
+//
+//     impl<T: Trait, U> Debug for Wrapper<T, U>
+//     where
+//         T::Value: Debug,
+//         U: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, String>>();
+
+    let wrapper = Wrapper::<Id, _> {
+        field: Field { values: vec![1, 2] },
+        normal: "n",
+    };
+    let debug = format!("{:?}", wrapper);
+    assert_eq!(debug, r#"Wrapper { field: Field { values: [1, 2] }, normal: "n" }"#);
+}
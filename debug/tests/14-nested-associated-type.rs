@@ -0,0 +1,43 @@
+// Associated types are found at any depth inside a field type, including
+// inside qualified paths, and are bounded directly so that the type parameter
+// itself does not need to be Debug.
+//
+//     impl<T: Iterator, U: Trait> Debug for Field<T, U>
+//     where
+//         T::Item: Debug,
+//         <U as Trait>::Value: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T: Iterator, U: Trait> {
+    items: Vec<Option<T::Item>>,
+    values: (u8, Box<[<U as Trait>::Value]>),
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Neither of these implements Debug, but their associated types do.
+    struct Iter;
+    struct Id;
+
+    impl Iterator for Iter {
+        type Item = u8;
+        fn next(&mut self) -> Option<u8> {
+            None
+        }
+    }
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Field<Iter, Id>>();
+}
@@ -0,0 +1,47 @@
+// CustomDebug also works on enums. Unit variants print their name, tuple
+// variants go through Formatter::debug_tuple and struct variants through
+// Formatter::debug_struct, exactly like the standard derive.
+//
+// Field attributes apply inside variants the same way they do on struct
+// fields, and #[debug(rename = "...")] on a variant changes the name that is
+// printed for it. Bounds are inferred from the fields of every variant.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub enum State<T, U> {
+    Idle,
+    Running(u32, #[debug = "0x{:02x}"] u8),
+    Failed {
+        code: i32,
+        #[debug = "{:?}!"]
+        reason: &'static str,
+    },
+    #[debug(rename = "Waiting")]
+    Blocked(T),
+    Marker(PhantomData<U>),
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    assert_debug::<State<u8, NotDebug>>();
+    assert_debug::<Never>();
+
+    type S = State<u8, NotDebug>;
+    assert_eq!(format!("{:?}", S::Idle), "Idle");
+    assert_eq!(format!("{:?}", S::Running(7, 10)), "Running(7, 0x0a)");
+    assert_eq!(
+        format!("{:?}", S::Failed { code: -1, reason: "eof" }),
+        r#"Failed { code: -1, reason: "eof"! }"#,
+    );
+    assert_eq!(format!("{:?}", S::Blocked(3)), "Waiting(3)");
+    assert!(format!("{:?}", S::Marker(PhantomData)).starts_with("Marker(PhantomData<"));
+}
@@ -0,0 +1,21 @@
+// Tuple structs are printed through Formatter::debug_tuple and unit structs as
+// just their name, matching the standard derive. Field attributes work on
+// tuple struct fields too.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Meters(f64);
+
+#[derive(CustomDebug)]
+pub struct Rgb(u8, #[debug = "0x{:02x}"] u8, u8);
+
+#[derive(CustomDebug)]
+pub struct Marker;
+
+fn main() {
+    assert_eq!(format!("{:?}", Meters(1.5)), "Meters(1.5)");
+    assert_eq!(format!("{:?}", Rgb(1, 255, 3)), "Rgb(1, 0xff, 3)");
+    assert_eq!(format!("{:?}", Marker), "Marker");
+    assert_eq!(format!("{:#?}", Meters(1.5)), "Meters(\n    1.5,\n)");
+}
@@ -0,0 +1,37 @@
+// Wrapper types tend to nest in logs as `UserId(UserId(42))`. With
+// #[debug(transparent)] a struct with a single field is formatted exactly as
+// that field, forwarding the caller's Formatter so that flags like `{:#?}` and
+// `{:x?}` still apply to the inner value.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Session {
+    user: UserId,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Flags(#[debug = "0b{:04b}"] u8);
+
+#[derive(CustomDebug)]
+pub struct Request {
+    session: Session,
+    tags: Vec<UserId>,
+}
+
+fn main() {
+    let request = Request {
+        session: Session { user: UserId(42) },
+        tags: vec![UserId(1), UserId(255)],
+    };
+
+    assert_eq!(format!("{:?}", request), "Request { session: 42, tags: [1, 255] }");
+    assert_eq!(format!("{:x?}", UserId(255)), "ff");
+    assert_eq!(format!("{:?}", Flags(5)), "0b0101");
+}
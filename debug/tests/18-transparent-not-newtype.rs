@@ -0,0 +1,19 @@
+// #[debug(transparent)] only makes sense when there is exactly one field to
+// forward to.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub enum Id {
+    User(u64),
+}
+
+fn main() {}
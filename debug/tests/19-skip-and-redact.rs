@@ -0,0 +1,80 @@
+// Secrets must never end up in logs.
+//
+// #[debug(skip)] leaves a field out entirely; the output then ends with `..`
+// through Formatter::finish_non_exhaustive so that readers can tell something
+// was omitted. #[debug(redact)] keeps the field name but prints "<redacted>"
+// in place of the value, and #[debug(redact = "len")] prints only the value's
+// length. None of these fields need to implement Debug.
+//
+// On the container, #[debug(redact_all_except(...))] redacts every field that
+// is not listed, which is less error-prone than annotating each field of a
+// wide struct.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub struct NotDebug;
+
+#[derive(CustomDebug)]
+pub struct Login<T> {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact = "len")]
+    token: Vec<u8>,
+    #[debug(skip)]
+    connection: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(redact_all_except(id, country))]
+pub struct Customer {
+    id: u64,
+    name: &'static str,
+    email: &'static str,
+    #[debug(redact = "len")]
+    address: &'static str,
+    country: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub enum Credential {
+    Password(#[debug(redact)] String),
+    Key {
+        id: u32,
+        #[debug(skip)]
+        secret: NotDebug,
+    },
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Login<NotDebug>>();
+
+    let login = Login {
+        user: "root",
+        password: "hunter2".to_owned(),
+        token: vec![0; 32],
+        connection: NotDebug,
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { user: "root", password: "<redacted>", token: <redacted len=32>, .. }"#,
+    );
+
+    let customer = Customer {
+        id: 7,
+        name: "Ada",
+        email: "ada@example.com",
+        address: "12 Analytical Row",
+        country: "UK",
+    };
+    assert_eq!(
+        format!("{:?}", customer),
+        r#"Customer { id: 7, name: "<redacted>", email: "<redacted>", address: <redacted len=17>, country: "UK" }"#,
+    );
+
+    assert_eq!(format!("{:?}", Credential::Password("x".to_owned())), r#"Password("<redacted>")"#);
+    assert_eq!(format!("{:?}", Credential::Key { id: 1, secret: NotDebug }), "Key { id: 1, .. }");
+}
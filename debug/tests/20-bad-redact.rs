@@ -0,0 +1,24 @@
+// Misuse of the skip and redact attributes is reported at the attribute.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Conflicting {
+    #[debug(skip, redact)]
+    password: String,
+}
+
+#[derive(CustomDebug)]
+pub struct UnknownMode {
+    #[debug(redact = "hash")]
+    password: String,
+}
+
+#[derive(CustomDebug)]
+#[debug(redact_all_except(id, nmae))]
+pub struct Typo {
+    id: u64,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,59 @@
+// Compact types read better with a template than with the field-by-field
+// debug_struct output. A container attribute #[debug("...")] gives a format
+// string for the whole value whose placeholders name fields, by name or by
+// position for tuple structs, with optional format specs after a colon:
+//
+//     #[derive(CustomDebug)]
+//     #[debug("Point({x}, {y:.2})")]
+//     pub struct Point {
+//         x: i32,
+//         y: f64,
+//     }
+//
+// The placeholders are formatted through the same traits as in format!, so
+// `{x}` uses Display and `{x:?}` uses Debug, and bounds are inferred on those
+// traits. Enum variants accept the same attribute.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+#[debug("Point({x}, {y:.2})")]
+pub struct Point {
+    x: i32,
+    y: f64,
+}
+
+#[derive(CustomDebug)]
+#[debug("{0}..{1} {{exclusive}}")]
+pub struct Range(u32, u32);
+
+#[derive(CustomDebug)]
+#[debug("{value:?} as {value:#x}")]
+pub struct Hex<T> {
+    value: T,
+    unused: std::marker::PhantomData<fn() -> T>,
+}
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    #[debug("circle r={radius}")]
+    Circle { radius: f32 },
+    #[debug("rect {0}x{1}")]
+    Rect(u32, u32),
+    Empty,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Hex<u8>>();
+
+    assert_eq!(format!("{:?}", Point { x: 1, y: 2.0 }), "Point(1, 2.00)");
+    assert_eq!(format!("{:?}", Range(3, 7)), "3..7 {exclusive}");
+    let hex = Hex { value: 255u8, unused: std::marker::PhantomData };
+    assert_eq!(format!("{:?}", hex), "255 as 0xff");
+    assert_eq!(format!("{:?}", Shape::Circle { radius: 1.5 }), "circle r=1.5");
+    assert_eq!(format!("{:?}", Shape::Rect(2, 3)), "rect 2x3");
+    assert_eq!(format!("{:?}", Shape::Empty), "Empty");
+}
@@ -0,0 +1,26 @@
+// Every placeholder in a template has to name a field that exists. The error
+// points at the template's string literal and names the offending
+// placeholder in the message. Spans inside a string literal are only
+// available to proc macros on nightly, so the error always points at the
+// whole literal, to behave the same on every toolchain.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug("Point({x}, {z})")]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+#[debug("{0} and {}")]
+pub struct Pair(u8, u8);
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    #[debug("rect {0}x{2}")]
+    Rect(u32, u32),
+}
+
+fn main() {}
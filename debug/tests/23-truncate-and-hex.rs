@@ -0,0 +1,52 @@
+// Printing a large buffer in full floods the logs.
+//
+// #[debug(truncate = N)] prints at most N elements of a sequence, or N
+// characters of a string, followed by `… (+M more)`. #[debug(hex)] prints
+// anything that is AsRef<[u8]> as a compact hex dump, and can be combined with
+// truncate.
+//
+// Both are implemented by Debug adapters in derive_debug::fmt. Since a crate
+// with the proc-macro crate type can only export macros, the derive itself
+// lives in the derive_debug_impl crate and is re-exported by derive_debug
+// alongside those adapters.
+
+use derive_debug::CustomDebug;
+use std::collections::VecDeque;
+
+#[derive(CustomDebug)]
+pub struct Packet<'a> {
+    #[debug(truncate = 3)]
+    samples: Vec<u32>,
+    #[debug(truncate = 5)]
+    text: &'a str,
+    #[debug(truncate = 5)]
+    short: String,
+    #[debug(hex)]
+    header: [u8; 4],
+    #[debug(hex, truncate = 2)]
+    payload: Vec<u8>,
+    #[debug(truncate = 1)]
+    queue: VecDeque<char>,
+}
+
+fn main() {
+    let packet = Packet {
+        samples: (0..100).collect(),
+        text: "hello, world",
+        short: "hey".to_owned(),
+        header: [0xde, 0xad, 0xbe, 0xef],
+        payload: vec![1, 2, 3, 4],
+        queue: VecDeque::from(['a', 'b']),
+    };
+
+    assert_eq!(
+        format!("{:?}", packet),
+        concat!(
+            r#"Packet { samples: [0, 1, 2, … (+97 more)], text: "hello"… (+7 more), short: "hey", "#,
+            r#"header: [de ad be ef], payload: [01 02 … (+2 more)], queue: ['a', … (+1 more)] }"#,
+        ),
+    );
+
+    let debug = derive_debug::fmt::Truncate::new(&[1, 2][..], 5);
+    assert_eq!(format!("{:?}", debug), "[1, 2]");
+}
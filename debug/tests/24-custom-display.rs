@@ -0,0 +1,53 @@
+// Error enums that derive CustomDebug usually need a Display impl as well.
+// #[derive(CustomDisplay)] generates one from the same kind of templates that
+// #[debug("...")] accepts, given as #[display("...")] on a struct or on each
+// enum variant. #[display(transparent)] forwards to the Display impl of the
+// only field instead.
+//
+// Bounds are inferred the same way as for CustomDebug, on whichever trait each
+// placeholder uses: Display for `{x}`, Debug for `{x:?}` and so on.
+
+use derive_debug::{CustomDebug, CustomDisplay};
+use std::fmt::Display;
+use std::io;
+
+#[derive(CustomDebug, CustomDisplay)]
+pub enum OpenError {
+    #[display("failed to open {path}: {source}")]
+    Io { path: &'static str, source: io::Error },
+    #[display("{0:?} is not a valid mode")]
+    Mode(char),
+    #[display(transparent)]
+    Other(Box<dyn std::error::Error>),
+    #[display("permission denied")]
+    Denied,
+}
+
+#[derive(CustomDisplay)]
+#[display("{name}={value}")]
+pub struct Pair<T> {
+    name: &'static str,
+    value: T,
+}
+
+#[derive(CustomDisplay)]
+#[display(transparent)]
+pub struct Wrapper<T>(T);
+
+fn assert_display<T: Display>() {}
+
+fn main() {
+    assert_display::<Pair<u8>>();
+    assert_display::<Wrapper<Pair<String>>>();
+
+    let err = OpenError::Io {
+        path: "/etc/shadow",
+        source: io::Error::new(io::ErrorKind::NotFound, "no such file"),
+    };
+    assert_eq!(err.to_string(), "failed to open /etc/shadow: no such file");
+    assert_eq!(OpenError::Mode('x').to_string(), "'x' is not a valid mode");
+    assert_eq!(OpenError::Other("boom".into()).to_string(), "boom");
+    assert_eq!(OpenError::Denied.to_string(), "permission denied");
+
+    assert_eq!(Wrapper(Pair { name: "a", value: 1 }).to_string(), "a=1");
+}
@@ -0,0 +1,22 @@
+// CustomDisplay has no sensible default output, so every struct or variant
+// without a template or `transparent` is an error at its name, and
+// `transparent` needs exactly one field to forward to.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+pub enum State {
+    #[display("idle")]
+    Idle,
+    Running(u32),
+    #[display(transparent)]
+    Failed { code: i32, reason: String },
+}
+
+#[derive(CustomDisplay)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {}
@@ -0,0 +1,69 @@
+// Log pipelines want key/value pairs or JSON rather than Rust's Debug syntax.
+//
+// #[debug(format = "logfmt")] and #[debug(format = "json")] on the container
+// generate inherent `debug_logfmt` and `debug_json` methods returning something
+// that implements Display. They walk the same fields as the Debug impl, so
+// skipped fields are left out and redacted ones stay redacted. Strings,
+// numbers and booleans are written natively, strings are quoted and escaped
+// where the format needs it, and anything else is written as the string its
+// Debug impl produces. Floats keep the precision of their own type, so an
+// f32 of 0.1 is written as 0.1, and NaN and the infinities are null in JSON.
+//
+// Fields whose types also derive CustomDebug are nested: as dotted keys in
+// logfmt and as nested objects in JSON. The child does not need a format of
+// its own for that.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Retry {
+    attempts: u32,
+    backoff_ms: f64,
+    jitter: f32,
+}
+
+#[derive(CustomDebug)]
+pub enum State {
+    Idle,
+    Failed { code: i32 },
+}
+
+#[derive(CustomDebug)]
+#[debug(format = "logfmt", format = "json")]
+pub struct Request<'a> {
+    method: &'static str,
+    path: String,
+    verbose: bool,
+    #[debug(redact)]
+    token: String,
+    #[debug(skip)]
+    _body: &'a [u8],
+    retry: Retry,
+    state: State,
+    tags: Vec<&'static str>,
+}
+
+fn main() {
+    let request = Request {
+        method: "GET",
+        path: "/search?q=a b".to_owned(),
+        verbose: true,
+        token: "hunter2".to_owned(),
+        _body: b"",
+        retry: Retry { attempts: 3, backoff_ms: 2.5, jitter: 0.1 },
+        state: State::Failed { code: -1 },
+        tags: vec!["say \"hi\"", "x"],
+    };
+
+    assert_eq!(
+        request.debug_logfmt().to_string(),
+        r#"method=GET path="/search?q=a b" verbose=true token=<redacted> retry.attempts=3 retry.backoff_ms=2.5 retry.jitter=0.1 state.variant=Failed state.code=-1 tags="[\"say \\\"hi\\\"\", \"x\"]""#,
+    );
+    assert_eq!(
+        request.debug_json().to_string(),
+        r#"{"method":"GET","path":"/search?q=a b","verbose":true,"token":"<redacted>","retry":{"attempts":3,"backoff_ms":2.5,"jitter":0.1},"state":{"variant":"Failed","code":-1},"tags":"[\"say \\\"hi\\\"\", \"x\"]"}"#,
+    );
+
+    let idle = Request { state: State::Idle, retry: Retry { attempts: 0, backoff_ms: 0.0, jitter: f32::NAN }, ..request };
+    assert!(idle.debug_json().to_string().contains(r#""jitter":null},"state":{"variant":"Idle"}"#));
+}
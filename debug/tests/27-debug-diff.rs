@@ -0,0 +1,91 @@
+// When an assertion on a large struct fails, the full Debug output of both
+// sides is hard to compare by eye.
+//
+// #[derive(CustomDebugDiff)] generates
+//
+//     pub fn debug_diff(&self, other: &Self) -> Option<String>
+//
+// which lists only the fields that differ, one per line, as their dotted path
+// followed by both values. Fields whose types also derive CustomDebugDiff are
+// compared field by field rather than as a whole, and two different variants
+// of an enum are reported by name.
+//
+// Fields are compared by what CustomDebug would print for them, so the same
+// #[debug(...)] field attributes apply: a custom format is what gets compared,
+// skipped fields are ignored, and redacted fields never show up. A truncated
+// field is still compared in full, so a difference past the cut is reported,
+// but it is printed truncated.
+
+use derive_debug::{CustomDebug, CustomDebugDiff};
+
+#[derive(CustomDebug, CustomDebugDiff)]
+pub struct Limits {
+    max_connections: u32,
+    #[debug = "{}ms"]
+    timeout: u64,
+}
+
+#[derive(CustomDebug, CustomDebugDiff)]
+pub enum Mode {
+    Standby,
+    Active { since: u32 },
+}
+
+#[derive(CustomDebug, CustomDebugDiff)]
+pub struct Config {
+    name: String,
+    limits: Limits,
+    mode: Mode,
+    #[debug(redact)]
+    password: String,
+    #[debug(skip)]
+    generation: u64,
+    tags: Vec<&'static str>,
+    #[debug(truncate = 2)]
+    samples: Vec<u32>,
+    #[debug(hex, truncate = 1)]
+    digest: [u8; 2],
+}
+
+fn config() -> Config {
+    Config {
+        name: "primary".to_owned(),
+        limits: Limits { max_connections: 10, timeout: 500 },
+        mode: Mode::Active { since: 3 },
+        password: "hunter2".to_owned(),
+        generation: 1,
+        tags: vec!["a"],
+        samples: vec![1, 2, 3],
+        digest: [0xaa, 0xbb],
+    }
+}
+
+fn main() {
+    let left = config();
+    let same = Config { password: "swordfish".to_owned(), generation: 2, ..config() };
+    assert_eq!(left.debug_diff(&same), None);
+
+    let right = Config {
+        limits: Limits { max_connections: 10, timeout: 750 },
+        mode: Mode::Active { since: 4 },
+        tags: vec!["a", "b"],
+        ..config()
+    };
+    assert_eq!(
+        left.debug_diff(&right).unwrap(),
+        "limits.timeout: 500ms != 750ms\n\
+         mode.since: 3 != 4\n\
+         tags: [\"a\"] != [\"a\", \"b\"]",
+    );
+
+    let past_cut = Config { samples: vec![1, 2, 4], digest: [0xaa, 0xcc], ..config() };
+    assert_eq!(
+        left.debug_diff(&past_cut).unwrap(),
+        "samples: [1, 2, … (+1 more)] != [1, 2, … (+1 more)]\n\
+         digest: [aa … (+1 more)] != [aa … (+1 more)]",
+    );
+
+    let standby = Config { mode: Mode::Standby, ..config() };
+    assert_eq!(left.debug_diff(&standby).unwrap(), "mode: variant Active != Standby");
+    assert_eq!(Mode::Standby.debug_diff(&Mode::Active { since: 0 }).unwrap(), "self: variant Standby != Active");
+}
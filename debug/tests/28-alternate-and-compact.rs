@@ -0,0 +1,48 @@
+// Pretty-printing with `{:#?}` should reach into custom field formats, and
+// small types should be able to opt out of it.
+//
+// A format string from #[debug = "..."] or a template gets the `#` flag added
+// to its `{:?}` placeholders when the value is formatted with `{:#?}`, so a
+// field printed as "ids={:?}" is pretty-printed along with everything else.
+// Functions given to #[debug(with = ...)] receive the Formatter and can check
+// f.alternate() themselves.
+//
+// #[debug(compact)] on the container keeps a type on one line even under
+// `{:#?}`, which suits small values like coordinates inside larger trees.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(compact)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+pub struct Shape {
+    #[debug = "ids={:?}"]
+    ids: Vec<u8>,
+    origin: Point,
+}
+
+#[derive(CustomDebug)]
+#[debug("Tags{tags:?}")]
+pub struct Tags {
+    tags: Vec<&'static str>,
+}
+
+fn main() {
+    let shape = Shape { ids: vec![1, 2], origin: Point { x: 1, y: 2 } };
+
+    assert_eq!(format!("{:?}", shape), "Shape { ids: ids=[1, 2], origin: Point { x: 1, y: 2 } }");
+    assert_eq!(
+        format!("{:#?}", shape),
+        "Shape {\n    ids: ids=[\n        1,\n        2,\n    ],\n    origin: Point { x: 1, y: 2 },\n}",
+    );
+    assert_eq!(format!("{:#?}", shape.origin), "Point { x: 1, y: 2 }");
+
+    let tags = Tags { tags: vec!["a"] };
+    assert_eq!(format!("{:?}", tags), "Tags[\"a\"]");
+    assert_eq!(format!("{:#?}", tags), "Tags[\n    \"a\",\n]");
+}
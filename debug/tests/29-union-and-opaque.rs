@@ -0,0 +1,51 @@
+// Unions and handles from FFI code usually have no meaningful field list.
+//
+// #[debug(with = path)] on the container formats the whole value through a
+// `fn(&Self, &mut Formatter) -> fmt::Result`, which is where the unsafe read
+// of a union field belongs: the function's author knows which field is valid.
+// #[debug(opaque)] prints only the type name, and #[debug(opaque(address))]
+// adds the address of the value as `Name @ 0x...`. Both work on structs as
+// well as unions, and neither needs the fields to implement Debug.
+
+use derive_debug::CustomDebug;
+use std::fmt;
+
+#[derive(CustomDebug)]
+#[debug(with = fmt_bits)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn fmt_bits(bits: &Bits, f: &mut fmt::Formatter) -> fmt::Result {
+    // Every bit pattern is a valid u32.
+    write!(f, "Bits({:#010x})", unsafe { bits.int })
+}
+
+pub struct RawHandle;
+
+#[derive(CustomDebug)]
+#[debug(opaque)]
+pub union Value {
+    handle: std::mem::ManuallyDrop<RawHandle>,
+    tag: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(opaque(address))]
+pub struct Context {
+    _raw: *mut RawHandle,
+}
+
+fn main() {
+    let bits = Bits { float: 1.0 };
+    assert_eq!(format!("{:?}", bits), "Bits(0x3f800000)");
+    assert_eq!(unsafe { bits.float }, 1.0);
+
+    let value = Value { tag: 1 };
+    assert_eq!(format!("{:?}", value), "Value");
+    assert_eq!(unsafe { value.tag }, 1);
+
+    let context = Context { _raw: std::ptr::null_mut() };
+    assert_eq!(format!("{:?}", context), format!("Context @ {:p}", &context));
+}
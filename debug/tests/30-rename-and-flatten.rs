@@ -0,0 +1,82 @@
+// Wire-level structs tend to have field names like `__pad0` or `hdr_v2` that
+// mean nothing in logs, and layers of small structs that make the output
+// deeply nested.
+//
+// #[debug(rename = "...")] prints a field under a different name. Renamed
+// fields keep their new name in the structured output and in diffs too.
+//
+// #[debug(flatten)] prints the fields of a struct that also derives
+// CustomDebug as if they were fields of the outer one. If the inner struct
+// skips any fields, the outer output ends with `..` as well. Flattening a
+// field of a generic type requires that type to derive CustomDebug, which is
+// expressed through a hidden `derive_debug::fmt::DebugFields` trait.
+//
+// In a diff, the fields of a flattened struct that also derives
+// CustomDebugDiff are reported as the outer struct's. One that doesn't is
+// compared as a whole under the field's own name. As with CustomDebug, a
+// flattened field of a generic type has to derive CustomDebugDiff.
+
+use derive_debug::{CustomDebug, CustomDebugDiff};
+
+#[derive(CustomDebug, CustomDebugDiff)]
+pub struct Header {
+    #[debug(rename = "version")]
+    hdr_v2: u8,
+    #[debug(skip)]
+    __pad0: [u8; 3],
+    length: u32,
+}
+
+#[derive(CustomDebug, CustomDebugDiff)]
+#[debug(format = "json")]
+pub struct Packet<H> {
+    #[debug(flatten)]
+    header: H,
+    #[debug(rename = "type")]
+    kind: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Timestamp {
+    secs: u64,
+}
+
+#[derive(CustomDebug, CustomDebugDiff)]
+pub struct Ack {
+    #[debug(flatten)]
+    sent: Timestamp,
+    seq: u32,
+}
+
+fn main() {
+    let packet = Packet {
+        header: Header { hdr_v2: 2, __pad0: [0; 3], length: 64 },
+        kind: "data",
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        r#"Packet { version: 2, length: 64, type: "data", .. }"#,
+    );
+    assert_eq!(
+        format!("{:#?}", packet),
+        "Packet {\n    version: 2,\n    length: 64,\n    type: \"data\",\n    ..\n}",
+    );
+    assert_eq!(packet.debug_json().to_string(), r#"{"version":2,"length":64,"type":"data"}"#);
+
+    let stamped = Packet { header: Timestamp { secs: 7 }, kind: "ack" };
+    assert_eq!(format!("{:?}", stamped), r#"Packet { secs: 7, type: "ack" }"#);
+
+    let longer = Packet {
+        header: Header { hdr_v2: 3, __pad0: [0; 3], length: 128 },
+        kind: "data",
+    };
+    assert_eq!(packet.debug_diff(&longer).unwrap(), "version: 2 != 3\nlength: 64 != 128");
+
+    let ack = Ack { sent: Timestamp { secs: 7 }, seq: 1 };
+    assert_eq!(format!("{:?}", ack), "Ack { secs: 7, seq: 1 }");
+    let later = Ack { sent: Timestamp { secs: 8 }, seq: 2 };
+    assert_eq!(
+        ack.debug_diff(&later).unwrap(),
+        "sent: Timestamp { secs: 7 } != Timestamp { secs: 8 }\nseq: 1 != 2",
+    );
+}
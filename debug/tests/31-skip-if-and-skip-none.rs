@@ -0,0 +1,53 @@
+// Structs with many optional fields print walls of `None`.
+//
+// #[debug(skip_if = path)] leaves a field out whenever the given
+// `fn(&T) -> bool` returns true for it. Unlike #[debug(skip)], this is about
+// hiding uninteresting values rather than secrets, so the output does not end
+// with `..` when a field is left out this way.
+//
+// #[debug(skip_none)] on the container does the same for every field whose
+// type is written as `Option<T>`, using the same check the builder derive uses
+// to recognize optional fields. A field with its own skip_if keeps it.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Query {
+    table: &'static str,
+    #[debug(skip_if = "Option::is_none")]
+    limit: Option<u32>,
+    #[debug(skip_if = Vec::is_empty)]
+    columns: Vec<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(skip_none, format = "logfmt")]
+pub struct Options {
+    verbose: Option<bool>,
+    depth: Option<u8>,
+    #[debug(skip_if = "is_zero")]
+    retries: u8,
+    name: &'static str,
+}
+
+#[derive(CustomDebug)]
+#[debug(skip_none)]
+pub struct Pair(Option<u8>, Option<u8>);
+
+fn is_zero(n: &u8) -> bool {
+    *n == 0
+}
+
+fn main() {
+    let query = Query { table: "users", limit: None, columns: Vec::new() };
+    assert_eq!(format!("{:?}", query), r#"Query { table: "users" }"#);
+
+    let query = Query { table: "users", limit: Some(10), columns: vec!["id"] };
+    assert_eq!(format!("{:?}", query), r#"Query { table: "users", limit: Some(10), columns: ["id"] }"#);
+
+    let options = Options { verbose: None, depth: Some(2), retries: 0, name: "x" };
+    assert_eq!(format!("{:?}", options), r#"Options { depth: Some(2), name: "x" }"#);
+    assert_eq!(options.debug_logfmt().to_string(), "depth=Some(2) name=x");
+
+    assert_eq!(format!("{:?}", Pair(None, Some(1))), "Pair(Some(1))");
+}
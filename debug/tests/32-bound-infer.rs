@@ -0,0 +1,45 @@
+// Bound inference cannot be right for every type. Recursive generics, fields
+// that are only printed through a function, or parameters that only matter
+// to callers can all call for something else.
+//
+// #[debug(bound(infer = "..."))] picks the strategy:
+//
+//   - "used", the default, bounds whatever the printed fields use outside of
+//     PhantomData, as in the earlier tests;
+//   - "all" bounds every type parameter on Debug, used or not, plus the
+//     associated types the printed fields use;
+//   - "none" bounds nothing.
+//
+// A container-level #[debug(bound = "...")] can be combined with any of them
+// to add predicates of its own. On its own, it implies "none".
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+#[debug(bound(infer = "all"))]
+pub struct Handle<T> {
+    id: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound(infer = "none"), bound = "T: Clone")]
+pub struct Cached<T> {
+    #[debug(skip)]
+    value: T,
+    hits: u64,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Handle<u8>>();
+    let handle: Handle<u8> = Handle { id: 3, _marker: PhantomData };
+    assert_eq!(format!("{:?}", handle), "Handle { id: 3, _marker: PhantomData<fn() -> u8> }");
+
+    let cached = Cached { value: String::new(), hits: 2 };
+    assert_eq!(format!("{:?}", cached), "Cached { hits: 2, .. }");
+    let _ = cached.value;
+}
@@ -0,0 +1,19 @@
+// Bounds given as strings are parsed when the macro runs, so a typo is
+// reported on the string literal itself rather than as a confusing error in
+// the generated impl. An unknown inference strategy is reported the same way.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "T: std::fmt::Debug +,, U")]
+pub struct Typo<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound(infer = "some"))]
+pub struct Strategy<T> {
+    value: T,
+}
+
+fn main() {}
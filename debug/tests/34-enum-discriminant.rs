@@ -0,0 +1,69 @@
+// Register and protocol enums are easier to debug with their numeric value
+// next to the variant name.
+//
+// #[debug(enum_discriminant)] on a `#[repr(u8)]` (or any other integer repr)
+// enum prints each variant as `Status::Busy (0x02)`. The radix can be chosen
+// with `enum_discriminant = "hex"`, the default, `"binary"` or `"decimal"`.
+// Hex and binary are padded to the width of the repr type and show the bit
+// pattern of negative values.
+//
+// Discriminants written as integer literals, and the ones that follow them
+// implicitly, are formatted when the macro runs. An enum with only unit
+// variants may also use other constant expressions; those variants are cast
+// to the repr type when printed instead.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(enum_discriminant)]
+#[repr(u8)]
+pub enum Status {
+    Idle,
+    Ready,
+    Busy = 0x02,
+    Error = 0x80,
+    Fatal,
+}
+
+#[derive(CustomDebug)]
+#[debug(enum_discriminant = "binary")]
+#[repr(i8)]
+pub enum Level {
+    Low = -1,
+    High = 1,
+}
+
+const BASE: u16 = 100;
+
+#[derive(CustomDebug)]
+#[debug(enum_discriminant = "decimal")]
+#[repr(u16)]
+pub enum Code {
+    Ok = BASE,
+    Retry = BASE + 1,
+}
+
+#[derive(CustomDebug)]
+#[debug(enum_discriminant)]
+#[repr(C, u16)]
+pub enum Frame {
+    Data { len: u16 } = 1,
+    Ack(u32),
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", Status::Idle), "Status::Idle (0x00)");
+    assert_eq!(format!("{:?}", Status::Busy), "Status::Busy (0x02)");
+    assert_eq!(format!("{:?}", Status::Fatal), "Status::Fatal (0x81)");
+    assert_eq!(format!("{:?}", Status::Ready), "Status::Ready (0x01)");
+    assert_eq!(format!("{:?}", Status::Error), "Status::Error (0x80)");
+
+    assert_eq!(format!("{:?}", Level::Low), "Level::Low (0b11111111)");
+    assert_eq!(format!("{:?}", Level::High), "Level::High (0b00000001)");
+
+    assert_eq!(format!("{:?}", Code::Ok), "Code::Ok (100)");
+    assert_eq!(format!("{:?}", Code::Retry), "Code::Retry (101)");
+
+    assert_eq!(format!("{:?}", Frame::Data { len: 3 }), "Frame::Data (0x0001) { len: 3 }");
+    assert_eq!(format!("{:?}", Frame::Ack(7)), "Frame::Ack (0x0002)(7)");
+}
@@ -0,0 +1,14 @@
+// Without a `#[repr(...)]` naming an integer type, the discriminant has no
+// fixed type or width to print, so #[debug(enum_discriminant)] is rejected at
+// compile time.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(enum_discriminant)]
+pub enum Status {
+    Idle,
+    Busy,
+}
+
+fn main() {}
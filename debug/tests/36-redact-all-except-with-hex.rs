@@ -0,0 +1,47 @@
+// #[debug(redact_all_except(...))] has to win over every other way a field
+// can be printed. A field marked #[debug(hex)] or #[debug(truncate = N)]
+// would otherwise show its bytes or its first few characters, which is
+// exactly what redaction is meant to prevent. This holds for the Debug impl,
+// the structured formats and CustomDebugDiff alike.
+
+use derive_debug::{CustomDebug, CustomDebugDiff};
+
+#[derive(CustomDebug, CustomDebugDiff)]
+#[debug(redact_all_except(user), format = "json", format = "logfmt")]
+pub struct Creds {
+    user: &'static str,
+    #[debug(hex)]
+    key: Vec<u8>,
+    #[debug(truncate = 4)]
+    token: String,
+    #[debug(hex, truncate = 1)]
+    salt: [u8; 2],
+}
+
+fn sample() -> Creds {
+    Creds {
+        user: "bob",
+        key: vec![0xde, 0xad],
+        token: "supersecret".to_owned(),
+        salt: [0xbe, 0xef],
+    }
+}
+
+fn main() {
+    let creds = sample();
+    assert_eq!(
+        format!("{:?}", creds),
+        r#"Creds { user: "bob", key: "<redacted>", token: "<redacted>", salt: "<redacted>" }"#,
+    );
+    assert_eq!(
+        creds.debug_json().to_string(),
+        r#"{"user":"bob","key":"<redacted>","token":"<redacted>","salt":"<redacted>"}"#,
+    );
+    assert_eq!(
+        creds.debug_logfmt().to_string(),
+        "user=bob key=<redacted> token=<redacted> salt=<redacted>",
+    );
+
+    let other = Creds { key: vec![0xbe, 0xef], token: "hunter2".to_owned(), salt: [0; 2], ..sample() };
+    assert_eq!(creds.debug_diff(&other), None);
+}
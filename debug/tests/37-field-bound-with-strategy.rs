@@ -0,0 +1,65 @@
+// A field-level #[debug(bound = "...")] replaces the bounds that field would
+// have contributed, and it keeps doing so whichever strategy the container
+// picks with #[debug(bound(infer = "..."))]. Under "all", bounding `T` alone
+// says nothing about `T::Value`, so an associated type used by a field is
+// still bounded, whether by inference or by the field's own predicate. Under
+// "none", the field's own predicate is the only one added for it.
+
+use derive_debug::{CustomDebug, CustomDebugDiff};
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+pub struct Id;
+
+impl Trait for Id {
+    type Value = u8;
+}
+
+#[derive(CustomDebug, CustomDebugDiff)]
+#[debug(bound(infer = "all"), format = "json")]
+pub struct Explicit<T: Trait> {
+    #[debug(bound = "T::Value: Debug")]
+    values: Vec<T::Value>,
+}
+
+#[derive(CustomDebug, CustomDebugDiff)]
+#[debug(bound(infer = "all"))]
+pub struct Inferred<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+#[derive(CustomDebug, CustomDebugDiff)]
+#[debug(bound(infer = "none"))]
+pub struct Nothing<T: Trait> {
+    #[debug(bound = "T::Value: Debug")]
+    values: Vec<T::Value>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+impl Debug for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("Id")
+    }
+}
+
+fn main() {
+    assert_debug::<Explicit<Id>>();
+    assert_debug::<Inferred<Id>>();
+    assert_debug::<Nothing<Id>>();
+
+    let explicit = Explicit::<Id> { values: vec![1, 2] };
+    assert_eq!(format!("{:?}", explicit), "Explicit { values: [1, 2] }");
+    assert_eq!(explicit.debug_json().to_string(), r#"{"values":"[1, 2]"}"#);
+    assert_eq!(explicit.debug_diff(&Explicit { values: vec![1] }).unwrap(), "values: [1, 2] != [1]");
+
+    let inferred = Inferred::<Id> { values: vec![3] };
+    assert_eq!(format!("{:?}", inferred), "Inferred { values: [3] }");
+    assert_eq!(inferred.debug_diff(&Inferred { values: vec![3] }), None);
+
+    let nothing = Nothing::<Id> { values: vec![] };
+    assert_eq!(format!("{:?}", nothing), "Nothing { values: [] }");
+}
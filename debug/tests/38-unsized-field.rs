@@ -0,0 +1,34 @@
+// The last field of a struct may be unsized, like the payload following a
+// fixed header. Values of such a struct only exist behind a reference, but
+// its Debug impl, its structured output and its diff print that field like
+// any other.
+
+use derive_debug::{CustomDebug, CustomDebugDiff};
+use std::fmt::Debug;
+
+#[derive(CustomDebug, CustomDebugDiff)]
+#[debug(format = "json")]
+pub struct Frame {
+    len: usize,
+    data: [u8],
+}
+
+#[derive(CustomDebug, CustomDebugDiff)]
+#[debug(format = "json")]
+pub struct Packet<T: ?Sized> {
+    len: usize,
+    data: T,
+}
+
+fn assert_debug<T: ?Sized + Debug>() {}
+
+fn main() {
+    assert_debug::<Frame>();
+
+    let packet: &Packet<[u8]> = &Packet { len: 3, data: [1u8, 2, 3] };
+    assert_eq!(format!("{:?}", packet), "Packet { len: 3, data: [1, 2, 3] }");
+    assert_eq!(packet.debug_json().to_string(), r#"{"len":3,"data":"[1, 2, 3]"}"#);
+
+    let other: &Packet<[u8]> = &Packet { len: 2, data: [1u8, 2] };
+    assert_eq!(packet.debug_diff(other).unwrap(), "len: 3 != 2\ndata: [1, 2, 3] != [1, 2]");
+}
@@ -1,12 +1,42 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse.rs");
-    //t.pass("tests/02-impl-debug.rs");
-    //t.pass("tests/03-custom-format.rs");
-    //t.pass("tests/04-type-parameter.rs");
-    //t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-impl-debug.rs");
+    t.pass("tests/03-custom-format.rs");
+    t.pass("tests/04-type-parameter.rs");
+    t.pass("tests/05-phantom-data.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.compile_fail("tests/09-unsupported-input.rs");
+    t.pass("tests/10-redefined-prelude-types.rs");
+    t.pass("tests/11-debug-with.rs");
+    t.compile_fail("tests/12-bad-format-string.rs");
+    t.pass("tests/13-field-bound.rs");
+    t.pass("tests/14-nested-associated-type.rs");
+    t.pass("tests/15-enum.rs");
+    t.pass("tests/16-tuple-and-unit-struct.rs");
+    t.pass("tests/17-transparent.rs");
+    t.compile_fail("tests/18-transparent-not-newtype.rs");
+    t.pass("tests/19-skip-and-redact.rs");
+    t.compile_fail("tests/20-bad-redact.rs");
+    t.pass("tests/21-template.rs");
+    t.compile_fail("tests/22-template-unknown-field.rs");
+    t.pass("tests/23-truncate-and-hex.rs");
+    t.pass("tests/24-custom-display.rs");
+    t.compile_fail("tests/25-display-missing-template.rs");
+    t.pass("tests/26-structured-output.rs");
+    t.pass("tests/27-debug-diff.rs");
+    t.pass("tests/28-alternate-and-compact.rs");
+    t.pass("tests/29-union-and-opaque.rs");
+    t.pass("tests/30-rename-and-flatten.rs");
+    t.pass("tests/31-skip-if-and-skip-none.rs");
+    t.pass("tests/32-bound-infer.rs");
+    t.compile_fail("tests/33-bad-bound.rs");
+    t.pass("tests/34-enum-discriminant.rs");
+    t.compile_fail("tests/35-enum-discriminant-without-repr.rs");
+    t.pass("tests/36-redact-all-except-with-hex.rs");
+    t.pass("tests/37-field-bound-with-strategy.rs");
+    t.pass("tests/38-unsized-field.rs");
 }
@@ -0,0 +1,60 @@
+use proc_macro2::{Group, Ident};
+use quote::ToTokens;
+use syn::{BinOp, Expr, UnOp};
+
+/// Evaluates the integer expression in a `#{ ... }` block, in which the
+/// variable stands for `value`. Only integer literals, the variable,
+/// parentheses, unary minus and `+ - * / % << >> & |` are allowed. The
+/// arithmetic is done in `i64`, and overflow or division by zero is an error
+/// pointing at the operation.
+pub(crate) fn eval(block: &Group, var: &Ident, value: i64) -> syn::Result<i64> {
+    let expr: Expr = syn::parse2(block.stream())?;
+    eval_expr(&expr, var, value)
+}
+
+fn eval_expr(expr: &Expr, var: &Ident, value: i64) -> syn::Result<i64> {
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }) => lit.base10_parse(),
+        Expr::Path(path) if path.path.is_ident(var) => Ok(value),
+        Expr::Paren(paren) => eval_expr(&paren.expr, var, value),
+        Expr::Group(group) => eval_expr(&group.expr, var, value),
+        Expr::Unary(syn::ExprUnary { op: UnOp::Neg(_), expr: operand, .. }) => {
+            let operand = eval_expr(operand, var, value)?;
+            operand.checked_neg().ok_or_else(|| overflow(expr))
+        }
+        Expr::Binary(binary) => {
+            let left = eval_expr(&binary.left, var, value)?;
+            let right = eval_expr(&binary.right, var, value)?;
+            let shift = || u32::try_from(right).ok();
+            let result = match binary.op {
+                BinOp::Add(_) => left.checked_add(right),
+                BinOp::Sub(_) => left.checked_sub(right),
+                BinOp::Mul(_) => left.checked_mul(right),
+                BinOp::Div(_) | BinOp::Rem(_) if right == 0 => {
+                    return Err(syn::Error::new_spanned(expr, "attempt to divide by zero"));
+                }
+                BinOp::Div(_) => left.checked_div(right),
+                BinOp::Rem(_) => left.checked_rem(right),
+                // Shifting bits out of the top, sign included, overflows.
+                BinOp::Shl(_) => shift().and_then(|shift| left.checked_shl(shift).filter(|result| result >> shift == left)),
+                BinOp::Shr(_) => shift().and_then(|shift| left.checked_shr(shift)),
+                BinOp::BitAnd(_) => Some(left & right),
+                BinOp::BitOr(_) => Some(left | right),
+                _ => return Err(unsupported(binary.op)),
+            };
+            result.ok_or_else(|| overflow(expr))
+        }
+        _ => Err(unsupported(expr)),
+    }
+}
+
+fn overflow(expr: &Expr) -> syn::Error {
+    syn::Error::new_spanned(expr, "this arithmetic operation overflows `i64`")
+}
+
+fn unsupported(tokens: impl ToTokens) -> syn::Error {
+    syn::Error::new_spanned(
+        tokens,
+        "expected an integer, the loop variable, parentheses, or one of `+ - * / % << >> & |`",
+    )
+}
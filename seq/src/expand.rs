@@ -0,0 +1,293 @@
+use proc_macro2::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+
+use crate::eval;
+use crate::parse::{IntFormat, Seq};
+
+pub(crate) fn expand(seq: &Seq) -> syn::Result<TokenStream> {
+    let values = seq.range.values();
+    if has_section(&seq.body) {
+        return expand_stream(&seq.body, seq, &values, None);
+    }
+    let mut output = TokenStream::new();
+    for &value in &values {
+        output.extend(expand_stream(&seq.body, seq, &values, Some(value))?);
+    }
+    Ok(output)
+}
+
+/// A `#( ... )*` section, or `#( ... ),*` with a separator between copies.
+struct Section<'a> {
+    body: &'a Group,
+    separator: Option<&'a TokenTree>,
+    /// How many tokens the section takes up, from `#` to `*`.
+    len: usize,
+}
+
+/// Whether `stream` contains a repetition section at any depth.
+fn has_section(stream: &TokenStream) -> bool {
+    let tokens: Vec<TokenTree> = stream.clone().into_iter().collect();
+    (0..tokens.len()).any(|i| {
+        section_at(&tokens, i).is_some()
+            || matches!(&tokens[i], TokenTree::Group(group) if has_section(&group.stream()))
+    })
+}
+
+/// The repetition section starting at `tokens[i]`, if there is one. The
+/// separator may be any single token other than a group or `*`, which would
+/// end the section instead.
+fn section_at(tokens: &[TokenTree], i: usize) -> Option<Section<'_>> {
+    let TokenTree::Punct(pound) = tokens.get(i)? else {
+        return None;
+    };
+    let TokenTree::Group(body) = tokens.get(i + 1)? else {
+        return None;
+    };
+    if pound.as_char() != '#' || body.delimiter() != Delimiter::Parenthesis {
+        return None;
+    }
+    match (tokens.get(i + 2), tokens.get(i + 3)) {
+        (Some(star), _) if is_star(star) => Some(Section { body, separator: None, len: 3 }),
+        (Some(separator), Some(star)) if !matches!(separator, TokenTree::Group(_)) && is_star(star) => {
+            Some(Section { body, separator: Some(separator), len: 4 })
+        }
+        _ => None,
+    }
+}
+
+fn is_star(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == '*')
+}
+
+/// Copies `stream`, replacing each repetition section with one copy of its
+/// contents per value. Outside of any section `value` is `None` and tokens
+/// are copied as they are; inside one, the variable is replaced by the value
+/// of that repetition, pasted identifiers such as `f~N~_suffix` are joined
+/// into one, `#{ ... }` blocks are evaluated, and `~N` inside string literals
+/// is replaced as well. A section nested in another repeats in full within
+/// each copy of the outer one, and the variable refers to the innermost
+/// repetition.
+fn expand_stream(stream: &TokenStream, seq: &Seq, values: &[i64], value: Option<i64>) -> syn::Result<TokenStream> {
+    let var = &seq.var;
+    let tokens: Vec<TokenTree> = stream.clone().into_iter().collect();
+    let mut output = TokenStream::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Some(section) = section_at(&tokens, i) {
+            for (n, &value) in values.iter().enumerate() {
+                if n > 0 {
+                    output.extend(section.separator.cloned());
+                }
+                output.extend(expand_stream(&section.body.stream(), seq, values, Some(value))?);
+            }
+            i += section.len;
+            continue;
+        }
+        if let Some(value) = value {
+            if let Some((ident, len)) = paste(&tokens[i..], var, value)? {
+                output.extend([TokenTree::Ident(ident)]);
+                i += len;
+                continue;
+            }
+            if let Some(block) = block_at(&tokens, i) {
+                let result = eval::eval(block, var, value)?;
+                output.extend(value_tokens(result, &seq.range.format, block.span(), tokens.get(i + 2)));
+                i += 2;
+                continue;
+            }
+        }
+        match (&tokens[i], value) {
+            (TokenTree::Ident(ident), Some(value)) if ident == var => {
+                output.extend(value_tokens(value, &seq.range.format, ident.span(), tokens.get(i + 1)));
+            }
+            (TokenTree::Literal(literal), Some(value)) => output.extend([substitute_str(literal, var, value)]),
+            (TokenTree::Group(group), _) => {
+                let stream = expand_stream(&group.stream(), seq, values, value)?;
+                output.extend([TokenTree::Group(respan_group(group, stream))]);
+            }
+            (token, _) => output.extend([token.clone()]),
+        }
+        i += 1;
+    }
+    Ok(output)
+}
+
+/// The literal that replaces the variable, written in the same base and with
+/// the same suffix as the range. A negative value is written as `-` and a
+/// literal, so it also works in patterns and generic arguments, and is
+/// parenthesized before `.` or `as`, which bind tighter than the minus:
+/// `N.abs()` must become `(-4).abs()`, not `-4.abs()`.
+fn value_tokens(value: i64, format: &IntFormat, span: Span, next: Option<&TokenTree>) -> TokenStream {
+    let mut literal: Literal = format.literal(value.unsigned_abs()).parse().unwrap();
+    literal.set_span(span);
+    if value >= 0 {
+        return TokenTree::Literal(literal).into_token_stream();
+    }
+    let mut minus = Punct::new('-', Spacing::Alone);
+    minus.set_span(span);
+    let negative = TokenStream::from_iter([TokenTree::Punct(minus), TokenTree::Literal(literal)]);
+    let binds_tighter = match next {
+        // A lone `.` is field or method access; `..` and `..=` start a range,
+        // whose bounds may not be parenthesized in a pattern.
+        Some(TokenTree::Punct(punct)) => punct.as_char() == '.' && punct.spacing() == Spacing::Alone,
+        Some(TokenTree::Ident(ident)) => ident == "as",
+        _ => false,
+    };
+    if !binds_tighter {
+        return negative;
+    }
+    let mut group = Group::new(Delimiter::Parenthesis, negative);
+    group.set_span(span);
+    TokenTree::Group(group).into_token_stream()
+}
+
+/// If `tokens` starts with pieces joined by `~`, like `Reg~N~Mask` or a
+/// leading `~N~_mask`, the identifier they form and how many tokens it took.
+/// A keyword never starts a paste, so `fn ~N~_f` is `fn` and a leading one.
+/// The identifier keeps the span of its first piece, so that errors about it
+/// point at the name in the body. In a lifetime such as `'a~N`, the
+/// apostrophe is a separate token and is left alone.
+fn paste(tokens: &[TokenTree], var: &Ident, value: i64) -> syn::Result<Option<(Ident, usize)>> {
+    let (mut text, span, mut len) = match tokens.first() {
+        Some(TokenTree::Ident(first)) if !is_keyword(first) && is_tilde(tokens.get(1)) && is_piece(&tokens[2..]) => {
+            (piece(&tokens[..1], var, value)?.0, first.span(), 1)
+        }
+        Some(TokenTree::Punct(tilde)) if is_tilde(tokens.first()) && is_piece(&tokens[1..]) => {
+            (String::new(), tilde.span(), 0)
+        }
+        _ => return Ok(None),
+    };
+    while is_tilde(tokens.get(len)) && is_piece(&tokens[len + 1..]) {
+        let (next, used) = piece(&tokens[len + 1..], var, value)?;
+        text.push_str(&next);
+        if !is_ident_prefix(&text) {
+            let tilde = tokens[len].span();
+            return Err(syn::Error::new(tilde, format!("pasting here produces `{}`, which is not a valid identifier", text)));
+        }
+        len += 1 + used;
+    }
+    if text == "_" {
+        return Err(syn::Error::new(span, "pasting here produces `_`, which is not a valid identifier"));
+    }
+    Ok(Some((Ident::new(&text, span), len)))
+}
+
+/// Whether `tokens` starts with something `piece` accepts.
+fn is_piece(tokens: &[TokenTree]) -> bool {
+    match tokens.first() {
+        Some(TokenTree::Ident(_)) => true,
+        Some(TokenTree::Group(group)) => group.delimiter() == Delimiter::Brace,
+        _ => block_at(tokens, 0).is_some(),
+    }
+}
+
+/// The text the start of `tokens` contributes to a pasted identifier, and how
+/// many tokens that took: the name of any identifier, or a number for the
+/// variable itself, a `#{ ... }` block or a `{N:02}` padding group.
+fn piece(tokens: &[TokenTree], var: &Ident, value: i64) -> syn::Result<(String, usize)> {
+    match &tokens[0] {
+        TokenTree::Ident(ident) if ident == var => Ok((value.to_string(), 1)),
+        TokenTree::Ident(ident) => Ok((ident.to_string(), 1)),
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => Ok((padded(group, var, value)?, 1)),
+        _ => Ok((eval::eval(block_at(tokens, 0).unwrap(), var, value)?.to_string(), 2)),
+    }
+}
+
+/// The braces of the `#{ ... }` block starting at `tokens[i]`, if there is
+/// one.
+fn block_at(tokens: &[TokenTree], i: usize) -> Option<&Group> {
+    match (tokens.get(i), tokens.get(i + 1)) {
+        (Some(TokenTree::Punct(pound)), Some(TokenTree::Group(group)))
+            if pound.as_char() == '#' && group.delimiter() == Delimiter::Brace =>
+        {
+            Some(group)
+        }
+        _ => None,
+    }
+}
+
+/// The number in a padding group after `~`, such as `Irq~{N:02}`, padded
+/// with zeros to the given number of digits: in decimal for `:02`, or in hex
+/// for `:x2` and `:X2`. The number is the variable or a `#{ ... }` block. The
+/// braces keep this apart from ordinary Rust: without them, `r~N: x2` in a
+/// struct literal would be read as a modifier.
+fn padded(group: &Group, var: &Ident, value: i64) -> syn::Result<String> {
+    let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+    let expected = || syn::Error::new(group.span(), format!("expected `{{{0}:02}}` or `{{{0}:x2}}`", var));
+    let (number, used) = match tokens.first() {
+        Some(TokenTree::Ident(ident)) if ident == var => (value, 1),
+        _ => match block_at(&tokens, 0) {
+            Some(block) => (eval::eval(block, var, value)?, 2),
+            None => return Err(expected()),
+        },
+    };
+    let spec = match &tokens[used..] {
+        [TokenTree::Punct(colon), spec] if colon.as_char() == ':' => spec.to_string(),
+        _ => return Err(expected()),
+    };
+    let (radix, width) = spec.split_at(usize::from(spec.starts_with(['x', 'X'])));
+    if width.is_empty() || !width.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(expected());
+    }
+    let width: usize = width.parse().map_err(|_| expected())?;
+    Ok(match radix {
+        "x" => format!("{:0width$x}", number),
+        "X" => format!("{:0width$X}", number),
+        _ => format!("{:0width$}", number),
+    })
+}
+
+fn is_keyword(ident: &Ident) -> bool {
+    syn::parse_str::<Ident>(&ident.to_string()).is_err()
+}
+
+fn is_tilde(token: Option<&TokenTree>) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == '~')
+}
+
+/// Whether `text` is a valid start of an identifier: a letter or underscore
+/// followed by letters, digits and underscores.
+fn is_ident_prefix(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Replaces `~N` with the value inside a string literal, as in `"field~N"`.
+/// Any other literal, or a string without `~N`, is returned unchanged.
+fn substitute_str(literal: &Literal, var: &Ident, value: i64) -> TokenTree {
+    let unchanged = TokenTree::Literal(literal.clone());
+    let Ok(lit) = syn::parse2::<syn::LitStr>(unchanged.clone().into()) else {
+        return unchanged;
+    };
+    let pattern = format!("~{}", var);
+    let text = lit.value();
+    let mut replaced = String::new();
+    let mut changed = false;
+    let mut rest = text.as_str();
+    while let Some(index) = rest.find(&pattern) {
+        let after = &rest[index + pattern.len()..];
+        replaced.push_str(&rest[..index]);
+        // `~NAME` does not refer to `N`.
+        if after.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            replaced.push_str(&pattern);
+        } else {
+            replaced.push_str(&value.to_string());
+            changed = true;
+        }
+        rest = after;
+    }
+    if !changed {
+        return unchanged;
+    }
+    replaced.push_str(rest);
+    let mut literal = Literal::string(&replaced);
+    literal.set_span(lit.span());
+    TokenTree::Literal(literal)
+}
+
+/// A group with the same delimiter and span as `group` around `stream`.
+fn respan_group(group: &Group, stream: TokenStream) -> Group {
+    let mut new = Group::new(group.delimiter(), stream);
+    new.set_span(group.span());
+    new
+}
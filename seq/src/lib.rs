@@ -1,8 +1,42 @@
 use proc_macro::TokenStream;
+use syn::parse_macro_input;
 
+mod eval;
+mod expand;
+mod parse;
+
+use crate::parse::Seq;
+
+/// Repeats a piece of code once for each integer in a range, as in
+/// `seq!(N in 0..4 { fn f~N() -> u64 { N * 2 } })`.
+///
+/// The range is written `A..B` or `A..=B` with integer literals, which may be
+/// negative. It can be reversed as `(A..B).rev()`, or written `B..=A rev` to
+/// count down from `B`, and `step K` after it keeps every `K`th value.
+///
+/// Within the body, every occurrence of the variable becomes an integer
+/// literal in the base and with the suffix the range was written in, so
+/// `0x00u8..0x10u8` gives `0x0fu8`. Pieces joined by `~` are pasted into one
+/// identifier, so `Reg~N~Mask` becomes `Reg0Mask`; this works in lifetimes
+/// like `'a~N` as well, and `~N` inside a string literal is replaced by the
+/// value. A pasted value can be zero-padded by putting it in braces with the
+/// number of digits, in decimal as `Irq~{N:02}` or in hex as `Reg~{N:x2}`.
+///
+/// A `#{ ... }` block is evaluated at expansion time and replaced by an
+/// integer literal, so `#{N * 4 + 1}` can be used where an expression would
+/// not do, or pasted as in `Field~#{N + 1}`. It supports `+ - * / % << >> &
+/// |` and parentheses, and overflow or division by zero is an error.
+///
+/// If the body contains `#( ... )*` sections, only those are repeated and the
+/// rest of the body appears once; otherwise the whole body is repeated. A
+/// token before the `*`, as in `#( ... ),*`, separates the copies. Sections
+/// may sit side by side or be nested, in which case the variable refers to
+/// the innermost one.
+///
+/// Generated tokens keep the spans of the tokens they were made from, so
+/// errors in the expansion point back at the body.
 #[proc_macro]
 pub fn seq(input: TokenStream) -> TokenStream {
-    let _ = input;
-
-    unimplemented!()
+    let seq = parse_macro_input!(input as Seq);
+    expand::expand(&seq).unwrap_or_else(syn::Error::into_compile_error).into()
 }
@@ -0,0 +1,226 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+
+/// The input to `seq!`: `N in 0..8 { ... }`.
+pub(crate) struct Seq {
+    pub var: syn::Ident,
+    pub range: Range,
+    pub body: TokenStream,
+}
+
+/// The values of a `seq!` range, counted upward from `start` to `end` and
+/// then reversed if `reverse` is set, keeping every `step`th one.
+pub(crate) struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub inclusive: bool,
+    pub reverse: bool,
+    pub step: usize,
+    pub format: IntFormat,
+}
+
+/// How the bounds of the range were written, so that substituted values are
+/// written the same way: `0x00..0x10` gives `0x0f`, and `0u8..16u8` gives
+/// `15u8`.
+#[derive(Clone)]
+pub(crate) struct IntFormat {
+    /// `0x`, `0o`, `0b` or nothing.
+    prefix: &'static str,
+    uppercase: bool,
+    /// The number of digits, if a bound was written with leading zeros.
+    width: usize,
+    suffix: String,
+}
+
+impl Parse for Seq {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let var = input.parse()?;
+        input.parse::<syn::Token![in]>()?;
+        let range = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let body = content.parse()?;
+        Ok(Seq { var, range, body })
+    }
+}
+
+/// `A..B` or `A..=B`, where either bound may be negative, as written before
+/// any `rev` or `step`.
+struct Bounds {
+    start: i64,
+    end: i64,
+    inclusive: bool,
+    format: IntFormat,
+    tokens: TokenStream,
+}
+
+impl Parse for Bounds {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let start_lit = input.parse::<syn::LitInt>()?;
+        let dots = if input.peek(syn::Token![..=]) {
+            input.parse::<syn::Token![..=]>()?.into_token_stream()
+        } else {
+            input.parse::<syn::Token![..]>()?.into_token_stream()
+        };
+        let end_lit = input.parse::<syn::LitInt>()?;
+        let suffix = match (start_lit.suffix(), end_lit.suffix()) {
+            (start, end) if !start.is_empty() && !end.is_empty() && start != end => {
+                return Err(syn::Error::new(end_lit.span(), format!("expected suffix `{}`, as on the start of the range", start)));
+            }
+            (start, "") => start,
+            (_, end) => end,
+        };
+        let (start_prefix, start_digits) = split_digits(&start_lit);
+        let (_, end_digits) = split_digits(&end_lit);
+        let padded = |digits: &str| if digits.len() > 1 && digits.starts_with('0') { digits.len() } else { 0 };
+        let format = IntFormat {
+            prefix: start_prefix,
+            uppercase: start_prefix == "0x" && format!("{}{}", start_digits, end_digits).chars().any(|c| c.is_ascii_uppercase()),
+            width: padded(&start_digits).max(padded(&end_digits)),
+            suffix: suffix.to_owned(),
+        };
+        Ok(Bounds {
+            start: start_lit.base10_parse()?,
+            end: end_lit.base10_parse()?,
+            inclusive: dots.to_string() == "..=",
+            format,
+            tokens: quote!(#start_lit #dots #end_lit),
+        })
+    }
+}
+
+/// The base prefix of an integer literal and its digits, without any sign,
+/// suffix or underscores.
+fn split_digits(lit: &syn::LitInt) -> (&'static str, String) {
+    let text = lit.to_string();
+    let text = text.trim_start_matches('-');
+    let text = &text[..text.len() - lit.suffix().len()];
+    let (prefix, digits) = match text.get(..2) {
+        Some("0x") => ("0x", &text[2..]),
+        Some("0o") => ("0o", &text[2..]),
+        Some("0b") => ("0b", &text[2..]),
+        _ => ("", text),
+    };
+    (prefix, digits.replace('_', ""))
+}
+
+impl Parse for Range {
+    /// Accepts `A..B`, `(A..B).rev()`, and `A..B rev` counting down from `A`
+    /// to `B`, each optionally followed by `step K`.
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut range;
+        let bounds;
+        if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            bounds = content.parse::<Bounds>()?;
+            input.parse::<syn::Token![.]>()?;
+            let rev = input.parse::<syn::Ident>()?;
+            if rev != "rev" {
+                return Err(syn::Error::new(rev.span(), "expected `rev`"));
+            }
+            let parens;
+            syn::parenthesized!(parens in input);
+            parens.parse::<syn::parse::Nothing>()?;
+            range = Range {
+                start: bounds.start,
+                end: bounds.end,
+                inclusive: bounds.inclusive,
+                reverse: true,
+                step: 1,
+                format: bounds.format.clone(),
+            };
+        } else {
+            bounds = input.parse::<Bounds>()?;
+            range = Range {
+                start: bounds.start,
+                end: bounds.end,
+                inclusive: bounds.inclusive,
+                reverse: false,
+                step: 1,
+                format: bounds.format.clone(),
+            };
+            if parse_keyword(input, "rev")? {
+                if bounds.start < bounds.end {
+                    return Err(syn::Error::new_spanned(
+                        &bounds.tokens,
+                        "`rev` counts down from the first bound to the second; use `(A..B).rev()` to reverse an increasing range",
+                    ));
+                }
+                // Counting down from `A` to `B` gives the same values as
+                // counting up over `B..=A` or `B+1..=A`, reversed.
+                range.start = if bounds.inclusive { bounds.end } else { bounds.end + 1 };
+                range.end = bounds.start;
+                range.inclusive = true;
+                range.reverse = true;
+            }
+        }
+        if parse_keyword(input, "step")? {
+            let step = input.parse::<syn::LitInt>()?;
+            range.step = step.base10_parse()?;
+            if range.step == 0 {
+                return Err(syn::Error::new(step.span(), "step must be greater than zero"));
+            }
+        }
+        if range.is_empty() {
+            return Err(syn::Error::new_spanned(&bounds.tokens, "this range is empty"));
+        }
+        Ok(range)
+    }
+}
+
+/// Consumes `keyword` if it comes next. `rev` and `step` are not reserved, so
+/// they are parsed as identifiers.
+fn parse_keyword(input: ParseStream, keyword: &str) -> syn::Result<bool> {
+    if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == keyword {
+        input.parse::<syn::Ident>()?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+impl Range {
+    /// Every value in the range, in order. The values are counted from the
+    /// first one by `step`, so a wide range with a large step stays cheap.
+    pub fn values(&self) -> Vec<i64> {
+        let Some((low, high)) = self.bounds() else {
+            return Vec::new();
+        };
+        let step = i64::try_from(self.step).ok();
+        let first = if self.reverse { high } else { low };
+        let next = |value: i64| match (step, self.reverse) {
+            (Some(step), true) => value.checked_sub(step),
+            (Some(step), false) => value.checked_add(step),
+            (None, _) => None,
+        };
+        std::iter::successors(Some(first), |&value| next(value))
+            .take_while(|value| (low..=high).contains(value))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds().is_none()
+    }
+
+    /// The lowest and highest value in the range, before stepping.
+    fn bounds(&self) -> Option<(i64, i64)> {
+        let high = if self.inclusive { self.end } else { self.end.checked_sub(1)? };
+        (self.start <= high).then_some((self.start, high))
+    }
+}
+
+impl IntFormat {
+    /// The literal for `magnitude`, without a sign.
+    pub fn literal(&self, magnitude: u64) -> String {
+        let width = self.width;
+        let digits = match (self.prefix, self.uppercase) {
+            ("0x", false) => format!("{:0width$x}", magnitude),
+            ("0x", true) => format!("{:0width$X}", magnitude),
+            ("0o", _) => format!("{:0width$o}", magnitude),
+            ("0b", _) => format!("{:0width$b}", magnitude),
+            _ => format!("{:0width$}", magnitude),
+        };
+        format!("{}{}{}", self.prefix, digits, self.suffix)
+    }
+}
@@ -0,0 +1,13 @@
+// The header in front of the body has to be a variable, the `in` keyword, and
+// a range between two integer literals. Anything else should be reported at
+// the offending token rather than as a panic inside the macro.
+
+use seq::seq;
+
+seq!(N from 0..4 {});
+
+seq!(N in 0..four {});
+
+seq!(N in 0..4 fn f() {});
+
+fn main() {}
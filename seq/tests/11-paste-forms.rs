@@ -0,0 +1,37 @@
+// Beyond `f~N`, the variable can be pasted anywhere in an identifier by
+// joining any number of pieces with `~`: `f~N~_suffix` becomes `f1_suffix`
+// and `Reg~N~Mask` becomes `Reg1Mask`. Pasting also works in the name of a
+// lifetime, as in `'a~N`, and `~N` inside a string literal is replaced by the
+// value, so `"field~N"` becomes "field1".
+
+use seq::seq;
+
+seq!(N in 0..3 {
+    const REG~N~_MASK: u32 = 1 << N;
+
+    #[derive(Debug)]
+    pub struct Reg~N~Mask;
+
+    fn name~N() -> &'static str {
+        "field~N"
+    }
+
+    fn first~N<'a~N>(s: &'a~N str) -> &'a~N str {
+        &s[..1]
+    }
+});
+
+seq!(N in 1..=2 {
+    // `~NAME` is not `~N`, and `N` itself is left alone inside strings.
+    const NOTE~N: &str = "N ~NAME ~N";
+});
+
+fn main() {
+    assert_eq!(REG0_MASK | REG1_MASK | REG2_MASK, 0b111);
+    assert_eq!(format!("{:?}", Reg2Mask), "Reg2Mask");
+    assert_eq!([name0(), name1(), name2()], ["field0", "field1", "field2"]);
+    assert_eq!(first0("abc"), "a");
+    assert_eq!(first2("xyz"), "x");
+    assert_eq!(NOTE1, "N ~NAME 1");
+    assert_eq!(NOTE2, "N ~NAME 2");
+}
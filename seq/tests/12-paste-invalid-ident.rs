@@ -0,0 +1,19 @@
+// Pasting the value at the front of an identifier makes it start with a
+// digit, which is not a valid identifier. The error points at the `~` that
+// pasted the digits in.
+//
+// A padding group in braces has to hold the variable, a colon and the
+// number of digits, optionally after `x` or `X` for hex. Anything else is
+// reported at the braces.
+
+use seq::seq;
+
+seq!(N in 0..2 {
+    fn ~N~_handler() {}
+});
+
+seq!(N in 0..2 {
+    fn handler~{N:y2}() {}
+});
+
+fn main() {}
@@ -0,0 +1,53 @@
+// A token between the section and its `*` is used as a separator: it goes
+// between the copies but not after the last one. This matters where a
+// trailing separator is not allowed, or means something else, such as in
+// some generic argument lists or in arithmetic. Any single token other than
+// a delimited group or `*` itself works as a separator.
+//
+//     seq!(N in 0..3 {
+//         fn call(#(a~N: u8),*) {}
+//     });
+//
+// expands to
+//
+//     fn call(a0: u8, a1: u8, a2: u8) {}
+//
+// A body may also contain any number of sections side by side, and sections
+// nested inside one another. A nested section is repeated in full within
+// each copy of the outer one, and the variable in it refers to the inner
+// repetition.
+
+use seq::seq;
+
+seq!(N in 0..3 {
+    fn call(#(a~N: u8),*) -> u32 {
+        0 #(+ a~N as u32)*
+    }
+
+    fn union(#(b~N: u32),*) -> u32 {
+        #(b~N)|*
+    }
+
+    type Triple = (#(u8),*);
+
+    #[derive(Debug, PartialEq)]
+    enum Cell {
+        #(Cell~N,)*
+    }
+
+    fn cells() -> [Cell; 3] {
+        [#(Cell::Cell~N),*]
+    }
+
+    fn grid() -> [[u32; 3]; 3] {
+        [#([#(N),*]),*]
+    }
+});
+
+fn main() {
+    assert_eq!(call(1, 2, 3), 6);
+    assert_eq!(union(1, 2, 4), 7);
+    let _: Triple = (0u8, 0u8, 0u8);
+    assert_eq!(cells(), [Cell::Cell0, Cell::Cell1, Cell::Cell2]);
+    assert_eq!(grid(), [[0, 1, 2]; 3]);
+}
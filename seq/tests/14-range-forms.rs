@@ -0,0 +1,51 @@
+// Besides plain `A..B` and `A..=B`, the range may
+//
+//   - skip values with `step K`, as in `0..64 step 4` for every fourth
+//     offset;
+//   - run backwards, either as `(0..8).rev()` like an iterator in Rust, or
+//     as `7..=0 rev`, which counts down from the first bound to the second;
+//   - have negative bounds, as in `-4..4`.
+//
+// Negative values are pasted in as `-` followed by a literal, with
+// parentheses added before a method call or cast, where the minus would
+// otherwise apply to too little, but not before `..` in a range:
+// `N.to_string()` has to become `(-4).to_string()`, because
+// `-4.to_string()` means `-(4.to_string())`.
+
+use seq::seq;
+
+fn collect(values: &[i64]) -> Vec<i64> {
+    values.to_vec()
+}
+
+fn main() {
+    assert_eq!(collect(&seq!(N in 0..16 step 4 { [#(N,)*] })), [0, 4, 8, 12]);
+    assert_eq!(collect(&seq!(N in (0..4).rev() { [#(N,)*] })), [3, 2, 1, 0]);
+    assert_eq!(collect(&seq!(N in 3..=0 rev { [#(N,)*] })), [3, 2, 1, 0]);
+    assert_eq!(collect(&seq!(N in 3..0 rev { [#(N,)*] })), [3, 2, 1]);
+    assert_eq!(collect(&seq!(N in (0..8).rev() step 3 { [#(N,)*] })), [7, 4, 1]);
+    assert_eq!(collect(&seq!(N in -2..=2 { [#(N,)*] })), [-2, -1, 0, 1, 2]);
+    assert_eq!(collect(&seq!(N in -1..=-3 rev { [#(N,)*] })), [-1, -2, -3]);
+
+    // Only the stepped values are counted, so a whole 32-bit address space
+    // in large steps expands as quickly as a short range.
+    let bases: [u32; 4] = seq!(N in 0x0000_0000u32..=0xffff_ffffu32 step 0x4000_0000 { [#(N,)*] });
+    assert_eq!(bases, [0, 0x4000_0000, 0x8000_0000, 0xc000_0000]);
+    let tops: [u32; 2] = seq!(N in (0u32..=0xffff_ffffu32).rev() step 0x8000_0000 { [#(N,)*] });
+    assert_eq!(tops, [0xffff_ffff, 0x7fff_ffff]);
+
+    // Precedence: method calls, casts and patterns.
+    assert_eq!(seq!(N in -4..-3 { N.to_string() }), "-4");
+    assert_eq!(seq!(N in -1..0 { N as i8 as u8 }), 255);
+    assert_eq!(seq!(N in -3..-2 { 2 - N * 2 }), 8);
+    let sign = |x: i64| seq!(N in -1..0 { match x { N => "minus one", _ => "other" } });
+    assert_eq!(sign(-1), "minus one");
+    assert_eq!(sign(1), "other");
+
+    // A range pattern starting at a negative value takes no parentheses.
+    let bucket = |x: i64| seq!(N in (-2..0).rev() { match x { #(N..=5 => N,)* _ => 9 } });
+    assert_eq!(bucket(-2), -2);
+    assert_eq!(bucket(3), -1);
+    assert_eq!(bucket(6), 9);
+    assert_eq!(seq!(N in -3..-2 { (N..0).collect::<Vec<i64>>() }), [-3, -2, -1]);
+}
@@ -0,0 +1,19 @@
+// A range that produces no values is almost certainly a mistake, as is a
+// step of zero, so both are errors pointing at the range. Since `rev` counts
+// down from the first bound, writing it after an increasing range gets a hint
+// about `(A..B).rev()`. Bounds with two different suffixes cannot both be
+// the type of the values.
+
+use seq::seq;
+
+seq!(N in 4..4 {});
+
+seq!(N in 0..8 step 0 {});
+
+seq!(N in 0..8 rev {});
+
+seq!(N in (0..8).reverse() {});
+
+seq!(N in 0u8..4u16 {});
+
+fn main() {}
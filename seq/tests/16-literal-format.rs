@@ -0,0 +1,65 @@
+// The literals substituted for the variable are written the way the range
+// was. With `0x00..0x10` they are hex with two digits, `0x00` through `0x0f`,
+// and with `0u8..16u8` they keep the `u8` suffix, so they have that type even
+// where nothing else would infer it.
+//
+// Pasted identifiers use decimal, but putting the variable in braces with a
+// number of digits pads it with zeros: `Irq~{N:02}` gives `Irq00` through
+// `Irq15`, and `Reg~{N:x2}` gives `Reg00` through `Reg0f` in hex (`X2` for
+// uppercase). Padded names sort the same way lexicographically as
+// numerically. The braces are required: `r~N: 02` or `r~N: x2` is an
+// ordinary struct literal field and keeps its value.
+
+use seq::seq;
+
+seq!(N in 0x00..0x10 {
+    const OFFSETS: [&str; 16] = [#(stringify!(N),)*];
+});
+
+seq!(N in 0u8..4u8 {
+    fn suffixed() -> [String; 4] {
+        // Without the suffix, `N.count_ones()` would be ambiguous.
+        [#(format!("{}:{}", N, N.count_ones()),)*]
+    }
+});
+
+seq!(N in 0..16 {
+    #[derive(Debug)]
+    enum Interrupt {
+        #(Irq~{N:02},)*
+    }
+
+    #(#[derive(Debug)] pub struct Reg~{N:x2};)*
+    #(const REG_~{N:X2}: u8 = N;)*
+
+    // A type after `:` is not padding.
+    fn sum(#(a~N: u8),*) -> u8 {
+        0 #(+ a~N)*
+    }
+
+    pub struct Regs {
+        #(r~N: u8,)*
+    }
+
+    // Nor is a value in a struct literal, even one named like padding.
+    fn regs() -> Regs {
+        Regs { #(r~N: 07,)* }
+    }
+
+    fn regs_from(x2: u8) -> Regs {
+        Regs { #(r~N: x2,)* }
+    }
+});
+
+fn main() {
+    assert_eq!(OFFSETS[0], "0x00");
+    assert_eq!(OFFSETS[15], "0x0f");
+    assert_eq!(suffixed(), ["0:0", "1:1", "2:1", "3:2"]);
+    assert_eq!(format!("{:?}", Interrupt::Irq07), "Irq07");
+    assert_eq!(format!("{:?}", Interrupt::Irq15), "Irq15");
+    assert_eq!(format!("{:?}", Reg0f), "Reg0f");
+    assert_eq!(REG_0A, 10);
+    assert_eq!(sum(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16), 136);
+    assert_eq!(regs().r0 + regs().r15, 14);
+    assert_eq!(regs_from(5).r1, 5);
+}
@@ -0,0 +1,41 @@
+// Generated code often needs a constant computed from the variable, such as
+// an array length or a const generic argument, where an expression like
+// `N * 4 + 1` would be awkward or not allowed. A `#{ ... }` block in the body
+// is evaluated by the macro and replaced by an integer literal, so
+// `#{N * 4 + 1}` becomes `5` when `N` is 1.
+//
+// The block may use integer literals, the variable, parentheses, unary minus
+// and the operators `+ - * / % << >> & |` with their usual precedence. Like
+// the variable, a block can also be pasted into an identifier, as in
+// `Field~#{N + 1}`, and padded, as in `Reg~{#{N * 4}:02}`.
+
+use seq::seq;
+
+pub struct Buffer<const LEN: usize>([u8; LEN]);
+
+seq!(N in 0..4 {
+    #(
+        pub struct Field~#{N + 1};
+
+        pub const MASK~N: u32 = #{1 << N};
+
+        pub fn buffer~N() -> Buffer<#{N * 4 + 1}> {
+            Buffer([0; #{(N + 1) * 4 - 3}])
+        }
+    )*
+
+    pub const SUMS: [i32; 4] = [#(#{N * (N + 1) / 2 % 5 | 8 & 12},)*];
+    pub const NEGATED: [i32; 4] = [#(#{-N - 1},)*];
+    pub const PADDED: [&str; 4] = [#(stringify!(Reg~{#{N * 4}:02}),)*];
+});
+
+fn main() {
+    let _: [Field1; 1] = [Field1];
+    let _ = Field4;
+    assert_eq!(MASK0 | MASK1 | MASK2 | MASK3, 0b1111);
+    assert_eq!(buffer0().0.len(), 1);
+    assert_eq!(buffer3().0.len(), 13);
+    assert_eq!(SUMS, [8, 9, 11, 9]);
+    assert_eq!(NEGATED, [-1, -2, -3, -4]);
+    assert_eq!(PADDED, ["Reg00", "Reg04", "Reg08", "Reg12"]);
+}
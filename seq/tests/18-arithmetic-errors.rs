@@ -0,0 +1,20 @@
+// Arithmetic in a `#{ ... }` block is checked while expanding. Dividing by
+// zero or overflowing `i64` is an error pointing at the operation that failed,
+// for the first value of the variable where it happens, and anything other
+// than integer arithmetic is rejected.
+
+use seq::seq;
+
+seq!(N in 0..4 {
+    const A~N: i64 = #{12 / (N - 2)};
+});
+
+seq!(N in 60..64 {
+    const B~N: i64 = #{(1 << N) * 2};
+});
+
+seq!(N in 0..4 {
+    const C~N: i64 = #{N.pow(2)};
+});
+
+fn main() {}
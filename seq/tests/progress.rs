@@ -1,13 +1,22 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse-header.rs");
-    //t.pass("tests/02-parse-body.rs");
-    //t.compile_fail("tests/03-expand-four-errors.rs");
-    //t.pass("tests/04-paste-ident.rs");
-    //t.pass("tests/05-repeat-section.rs");
-    //t.pass("tests/06-init-array.rs");
-    //t.pass("tests/07-inclusive-range.rs");
-    //t.compile_fail("tests/08-ident-span.rs");
-    //t.pass("tests/09-interaction-with-macrorules.rs");
+    t.pass("tests/01-parse-header.rs");
+    t.pass("tests/02-parse-body.rs");
+    t.compile_fail("tests/03-expand-four-errors.rs");
+    t.pass("tests/04-paste-ident.rs");
+    t.pass("tests/05-repeat-section.rs");
+    t.pass("tests/06-init-array.rs");
+    t.pass("tests/07-inclusive-range.rs");
+    t.compile_fail("tests/08-ident-span.rs");
+    t.pass("tests/09-interaction-with-macrorules.rs");
+    t.compile_fail("tests/10-malformed-header.rs");
+    t.pass("tests/11-paste-forms.rs");
+    t.compile_fail("tests/12-paste-invalid-ident.rs");
+    t.pass("tests/13-section-separators.rs");
+    t.pass("tests/14-range-forms.rs");
+    t.compile_fail("tests/15-bad-range.rs");
+    t.pass("tests/16-literal-format.rs");
+    t.pass("tests/17-arithmetic.rs");
+    t.compile_fail("tests/18-arithmetic-errors.rs");
 }